@@ -11,8 +11,11 @@ import logging
 import os
 import re
 import shutil
+import signal
 import subprocess
+import sys
 import tempfile
+import threading
 import time
 from abc import ABC, abstractmethod
 from dataclasses import dataclass, field
@@ -26,10 +29,10 @@ def _get_agent_logger() -> logging.Logger:
     if not logger.handlers:
         log_dir = Path.home() / ".local" / "state" / "ralph"
         log_dir.mkdir(parents=True, exist_ok=True)
-        handler = logging.FileHandler(log_dir / "agent.log")
+        # Opened on first record, so `--log-level off` leaves no file behind
+        handler = logging.FileHandler(log_dir / "agent.log", delay=True)
         handler.setFormatter(logging.Formatter("%(asctime)s %(levelname)s %(message)s"))
         logger.addHandler(handler)
-        logger.setLevel(logging.DEBUG)
     return logger
 
 
@@ -37,6 +40,23 @@ COMPLETION_SIGNAL = "<promise>COMPLETE</promise>"
 VALID_AGENTS = ("claude", "opencode")
 DEFAULT_OPENCODE_MODEL = "anthropic/claude-opus-4-5"
 
+# Failure classes that need different handling than a plain retry.
+# Matched against error text from the CLI or API only (see process_error),
+# never the agent's own output. Checked in order, so account problems win
+# over a rate limit in the same message.
+ERROR_SIGNATURES: dict[str, tuple[str, ...]] = {
+    "auth": (
+        r"unauthorized",
+        r"authentication failed",
+        r"authentication_error",
+        r"invalid api key",
+        r"please run /login",
+    ),
+    "credit": (r"credit balance", r"quota exceeded"),
+    "rate_limit": (r"rate[ _]limit", r"usage limit"),
+    "overloaded": (r"overloaded",),
+}
+
 
 @dataclass
 class AgentResult:
@@ -48,6 +68,9 @@ class AgentResult:
     completed: bool = False
     failed: bool = False
     error_message: str = ""
+    # error_message came from the CLI or API (exit status, stream-json error
+    # result), not from scanning the agent's own output
+    process_error: bool = False
 
 
 @dataclass
@@ -60,6 +83,45 @@ class AgentConfig:
     verbose: bool = False
     model: str = ""
     interactive_mode: bool = False
+    settings_file: Path | None = None  # Claude --settings override
+    extra_env: dict[str, str] = field(default_factory=dict)  # CLI --env
+    capture_file: Path | None = None  # Append raw agent output here
+    idle_timeout: float = 0  # Kill the agent after this long without output (0 = off)
+    completion_signal: str = COMPLETION_SIGNAL  # CLI --complete-marker
+
+
+def classify_error(text: str) -> str | None:
+    """Classify agent error output by known error signatures.
+
+    Args:
+        text: Error text reported by the agent CLI or API.
+
+    Returns:
+        "auth", "credit", "rate_limit" or "overloaded", or None if the text
+        matches no known signature.
+    """
+    for kind, patterns in ERROR_SIGNATURES.items():
+        for pattern in patterns:
+            if re.search(pattern, text, re.IGNORECASE):
+                return kind
+    return None
+
+
+def _send_prompt(process: subprocess.Popen[str], prompt: str) -> None:
+    """Write the prompt to the agent's stdin and close it.
+
+    Also drops the process's stdin handle: communicate() flushes any stdin
+    it still holds, and flushing an already-closed pipe raises ValueError.
+    """
+    if process.stdin is None:
+        return
+    try:
+        if prompt:
+            process.stdin.write(prompt)
+        process.stdin.close()
+    except BrokenPipeError:
+        pass  # Exited before reading the prompt; exit code says why
+    process.stdin = None
 
 
 class Agent(ABC):
@@ -94,7 +156,11 @@ class Agent(ABC):
         ...
 
     @abstractmethod
-    def get_output(self, process: subprocess.Popen[str]) -> AgentResult:
+    def get_output(
+        self,
+        process: subprocess.Popen[str],
+        completion_signal: str = COMPLETION_SIGNAL,
+    ) -> AgentResult:
         """Get the result after the agent has completed.
 
         Blocks until the process terminates if it hasn't already, then
@@ -102,6 +168,7 @@ class Agent(ABC):
 
         Args:
             process: The agent subprocess (may still be running).
+            completion_signal: Output that marks all work as done.
 
         Returns:
             Structured result with output, exit code, and completion status.
@@ -129,14 +196,11 @@ class Agent(ABC):
         start_time = time.time()
         try:
             process = self.start(config)
-            # Write prompt to stdin and close it
-            if process.stdin is not None:
-                process.stdin.write(config.prompt)
-                process.stdin.close()
+            _send_prompt(process, config.prompt)
             # Wait for completion
             while not self.is_done(process):
                 time.sleep(0.1)
-            result = self.get_output(process)
+            result = self.get_output(process, config.completion_signal)
             result.duration_seconds = time.time() - start_time
             return result
         except OSError as e:
@@ -237,16 +301,23 @@ class ClaudeAgent(Agent):
         """Check if Claude process has terminated."""
         return process.poll() is not None
 
-    def get_output(self, process: subprocess.Popen[str]) -> AgentResult:
+    def get_output(
+        self,
+        process: subprocess.Popen[str],
+        completion_signal: str = COMPLETION_SIGNAL,
+    ) -> AgentResult:
         """Wait for Claude to finish and parse the result."""
         stdout, stderr = process.communicate()
         exit_code = process.returncode
 
         output = self._parse_stream_json(stdout)
-        completed = COMPLETION_SIGNAL in output
-        failed = self._detect_failure(exit_code, output, stderr)
+        api_error = self._parse_stream_json_error(stdout)
+        completed = completion_signal in output
+        failed = self._detect_failure(exit_code, output, stderr) or bool(api_error)
         error_message = ""
-        if failed:
+        if api_error:
+            error_message = api_error
+        elif failed:
             error_message = self._extract_error(exit_code, output, stderr)
 
         return AgentResult(
@@ -256,6 +327,7 @@ class ClaudeAgent(Agent):
             completed=completed,
             failed=failed,
             error_message=error_message,
+            process_error=exit_code != 0 or bool(api_error),
         )
 
     def _build_command(self, config: AgentConfig) -> list[str]:
@@ -265,6 +337,9 @@ class ClaudeAgent(Agent):
         """
         cmd = ["claude", "--print", "--output-format", "stream-json"]
 
+        if config.settings_file:
+            cmd.extend(["--settings", str(config.settings_file)])
+
         if config.yolo_mode:
             cmd.append("--dangerously-skip-permissions")
 
@@ -294,22 +369,39 @@ class ClaudeAgent(Agent):
                 text=True,
                 env=env,
                 cwd=str(config.working_dir),
+                # Own process group, so an idle kill takes its tools down too
+                start_new_session=config.idle_timeout > 0,
             )
 
-            # Write prompt to stdin and close
-            if process.stdin is not None:
-                process.stdin.write(config.prompt)
-                process.stdin.close()
-
-            # Wait for completion, reading stdout
-            stdout, _ = process.communicate()
+            # Send the prompt on stdin and wait for completion, reading stdout.
+            # communicate() closes stdin itself; closing it beforehand makes
+            # communicate() fail flushing the already-closed pipe.
+            idle_killed = False
+            if config.idle_timeout > 0:
+                stdout, idle_killed = self._communicate_with_idle_timeout(
+                    process, config.prompt, config.idle_timeout
+                )
+            else:
+                stdout, _ = process.communicate(input=config.prompt)
             exit_code = process.returncode
 
+            if config.capture_file:
+                self._write_capture(config.capture_file, stdout)
+
             output = self._parse_stream_json(stdout)
-            completed = COMPLETION_SIGNAL in output
-            failed = self._detect_failure(exit_code, output, "")
+            api_error = self._parse_stream_json_error(stdout)
+            completed = config.completion_signal in output
+            failed = self._detect_failure(exit_code, output, "") or bool(api_error)
             error_message = ""
-            if failed:
+            if idle_killed:
+                failed = True
+                error_message = (
+                    f"No output for {config.idle_timeout:g}s, Claude appears hung "
+                    "(stopped by --idle-timeout)"
+                )
+            elif api_error:
+                error_message = api_error
+            elif failed:
                 error_message = self._extract_error(exit_code, output, "")
 
             return AgentResult(
@@ -319,6 +411,7 @@ class ClaudeAgent(Agent):
                 completed=completed,
                 failed=failed,
                 error_message=error_message,
+                process_error=exit_code != 0 or bool(api_error),
             )
         except OSError as e:
             return AgentResult(
@@ -329,12 +422,75 @@ class ClaudeAgent(Agent):
                 error_message=f"Failed to start Claude: {e}",
             )
 
+    def _write_capture(self, capture_file: Path, stdout: str) -> None:
+        """Append the raw stream-json to the --capture-dir file.
+
+        A capture that cannot be written only costs the debugging copy, so
+        it is reported as a warning instead of failing the iteration.
+        """
+        try:
+            capture_file.parent.mkdir(parents=True, exist_ok=True)
+            with open(capture_file, "a") as f:
+                f.write(stdout)
+        except OSError as e:
+            print(f"Warning: could not write {capture_file}: {e}", file=sys.stderr)
+
+    def _communicate_with_idle_timeout(
+        self, process: subprocess.Popen[str], prompt: str, idle_timeout: float
+    ) -> tuple[str, bool]:
+        """Like communicate(), but kill the process if stdout goes quiet.
+
+        stream-json emits a line per message and tool call, so a long silence
+        means Claude is stuck rather than busy. Claude runs in its own
+        session, so the whole process group is killed: a hung tool is the
+        usual cause, and left alive it would keep touching the repo and hold
+        stdout open.
+
+        Returns:
+            The collected stdout, and whether the process was killed.
+        """
+        chunks: list[str] = []
+        last_output = time.time()
+
+        def read_stdout() -> None:
+            nonlocal last_output
+            if process.stdout is None:
+                return
+            for line in process.stdout:
+                chunks.append(line)
+                last_output = time.time()
+
+        reader = threading.Thread(target=read_stdout, daemon=True)
+        reader.start()
+        if process.stdin is not None:
+            try:
+                process.stdin.write(prompt)
+                process.stdin.close()
+            except BrokenPipeError:
+                pass  # Exited before reading the prompt; exit code says why
+
+        killed = False
+        while process.poll() is None:
+            if time.time() - last_output > idle_timeout:
+                try:
+                    os.killpg(process.pid, signal.SIGKILL)
+                except ProcessLookupError:
+                    pass  # Exited just now
+                process.wait()
+                killed = True
+                break
+            time.sleep(0.5)
+        reader.join(timeout=5)
+        return "".join(chunks), killed
+
     def _build_env(self, config: AgentConfig) -> dict[str, str]:
         """Build the environment for Claude subprocess."""
         env = os.environ.copy()
         if config.model:
             # Claude CLI doesn't support model selection, but log awareness
             env["RALPH_MODEL_OVERRIDE"] = config.model
+        # User-supplied variables go last so they can override anything above
+        env.update(config.extra_env)
         return env
 
     def _parse_stream_json(self, raw_output: str) -> str:
@@ -352,6 +508,25 @@ class ClaudeAgent(Agent):
         # Fall back to raw output
         return raw_output
 
+    def _parse_stream_json_error(self, raw_output: str) -> str:
+        """Get the error text of a stream-json result flagged is_error.
+
+        API failures (rate limits, bad credentials) arrive this way, so the
+        text is the CLI's error rather than anything the agent wrote.
+        Returns an empty string when the run did not end in an error.
+        """
+        for line in raw_output.splitlines():
+            if '"is_error"' not in line:
+                continue
+            try:
+                data: dict[str, Any] = json.loads(line)
+            except json.JSONDecodeError:
+                continue
+            if data.get("type") == "result" and data.get("is_error"):
+                lines = str(data.get("result", "")).strip().splitlines()
+                return lines[0][:200] if lines else "API error"
+        return ""
+
 
 class OpencodeAgent(Agent):
     """Agent implementation for OpenCode CLI with signal-file based completion.
@@ -425,13 +600,17 @@ class OpencodeAgent(Agent):
 
         return False
 
-    def get_output(self, process: subprocess.Popen[str]) -> AgentResult:
+    def get_output(
+        self,
+        process: subprocess.Popen[str],
+        completion_signal: str = COMPLETION_SIGNAL,
+    ) -> AgentResult:
         """Wait for OpenCode to finish and parse the result."""
         stdout, stderr = process.communicate()
         exit_code = process.returncode
 
         output = self._parse_output(stdout)
-        completed = COMPLETION_SIGNAL in output
+        completed = completion_signal in output
         failed = self._detect_failure(exit_code, output, stderr)
         error_message = ""
         if failed:
@@ -447,6 +626,7 @@ class OpencodeAgent(Agent):
             completed=completed,
             failed=failed,
             error_message=error_message,
+            process_error=exit_code != 0,
         )
 
     def run(self, config: AgentConfig) -> AgentResult:
@@ -462,8 +642,7 @@ class OpencodeAgent(Agent):
         try:
             process = self.start(config)
             # Close stdin - prompt is passed as CLI arg, not via stdin
-            if process.stdin is not None:
-                process.stdin.close()
+            _send_prompt(process, "")
 
             # Wait for signal file (clean completion) or process exit (crash).
             # session.idle fires ONLY at true completion — no debounce needed.
@@ -501,7 +680,7 @@ class OpencodeAgent(Agent):
                     process.returncode,
                 )
 
-            result = self.get_output(process)
+            result = self.get_output(process, config.completion_signal)
             result.duration_seconds = time.time() - start_time
             return result
         except OSError as e:
@@ -632,6 +811,9 @@ class OpencodeAgent(Agent):
         if config.verbose:
             env["RALPH_DEBUG"] = "1"
 
+        # User-supplied variables go last so they can override anything above
+        env.update(config.extra_env)
+
         return env
 
     def run_in_terminal(self, config: AgentConfig) -> AgentResult:
@@ -1,7 +1,7 @@
 """Attach command: connect to a running ralph session.
 
 Dispatches by session_type:
-- tmux: tmux attach-session -t <name>
+- tmux: tmux attach-session -t <name> (-r for --read-only)
 - opencode-server: opencode attach http://localhost:14096 (systemd server)
 """
 
@@ -46,6 +46,7 @@ def _find_most_recent_session(db: SessionDB) -> SessionInfo | None:
 def attach(
     task_name: str | None = None,
     session_id: str | None = None,
+    read_only: bool = False,
 ) -> int:
     """Attach to a running ralph session.
 
@@ -58,6 +59,7 @@ def attach(
             recently active session across all tasks.
         session_id: Optional specific opencode session ID to attach to.
             Overrides the stored session ID for opencode-server sessions.
+        read_only: Watch without forwarding keyboard input (tmux only).
 
     Returns:
         Exit code (0 = normal exit, 1 = error).
@@ -94,11 +96,19 @@ def attach(
         return 1
 
     if session.session_type == "opencode-server":
+        if read_only:
+            # opencode attach has no view-only mode; refuse rather than
+            # silently giving the user an interactive session
+            print(
+                "Error: --read-only is only supported for tmux sessions.",
+                file=sys.stderr,
+            )
+            return 1
         # Use provided session_id or fall back to stored one
         effective_session_id = session_id or session.opencode_session_id
         return _attach_opencode_server(task_name, effective_session_id, db)
     else:
-        return _attach_tmux(task_name, db)
+        return _attach_tmux(task_name, db, read_only)
 
 
 def _attach_opencode_server(
@@ -173,12 +183,13 @@ def _attach_opencode_server(
     return result.returncode
 
 
-def _attach_tmux(task_name: str, db: SessionDB) -> int:
+def _attach_tmux(task_name: str, db: SessionDB, read_only: bool = False) -> int:
     """Attach to a tmux session.
 
     Args:
         task_name: The task name.
         db: Session database.
+        read_only: Attach as a read-only tmux client.
 
     Returns:
         Exit code.
@@ -230,4 +241,6 @@ def _attach_tmux(task_name: str, db: SessionDB) -> int:
         return 1
 
     # Attach to the tmux session
-    return tmux_attach_session(session_name)
+    if read_only:
+        print("Attaching read-only (keyboard input is ignored)...")
+    return tmux_attach_session(session_name, read_only=read_only)
@@ -176,16 +176,21 @@ def verify_on_branch(expected_branch: str, cwd: str | None = None) -> None:
         )
 
 
-def handle_completion(config: BranchConfig) -> None:
+def handle_completion(config: BranchConfig, allow_merge: bool = False) -> str | None:
     """Handle branch operations at loop completion.
 
     Logic:
     - If mergeTarget is set and autoMerge is true: create PR and merge
+      (only with allow_merge, from --allow-merge; otherwise PR only)
     - If mergeTarget is set and autoMerge is false: create PR only
     - If no mergeTarget: do nothing (branch left as-is)
+
+    Returns:
+        A short result for the run summary (e.g. "merged into main"), or
+        None when there was nothing to do.
     """
     if not config.merge_target:
-        return
+        return None
 
     task_branch = config.branch_name
     target = config.merge_target
@@ -194,12 +199,17 @@ def handle_completion(config: BranchConfig) -> None:
     print(f"\n  Pushing branch {task_branch}...")
     _run_git("push", "-u", "origin", task_branch)
 
-    if config.auto_merge:
+    if config.auto_merge and allow_merge:
         print(f"  Creating PR and merging into {target}...")
-        _create_and_merge_pr(task_branch, target)
-    else:
-        print(f"  Creating PR targeting {target}...")
-        _create_pr(task_branch, target)
+        if _create_and_merge_pr(task_branch, target):
+            return f"merged into {target}"
+        return f"merge into {target} failed"
+
+    if config.auto_merge:
+        print("  autoMerge is set but --allow-merge was not given; leaving PR open.")
+    print(f"  Creating PR targeting {target}...")
+    url = _create_pr(task_branch, target)
+    return f"PR opened: {url}" if url else "PR not created"
 
 
 def _create_pr(branch: str, target: str) -> str:
@@ -239,11 +249,11 @@ def _create_pr(branch: str, target: str) -> str:
         return ""
 
 
-def _create_and_merge_pr(branch: str, target: str) -> None:
-    """Create a PR and merge it."""
+def _create_and_merge_pr(branch: str, target: str) -> bool:
+    """Create a PR and merge it. Returns True if the merge succeeded."""
     url = _create_pr(branch, target)
     if not url:
-        return
+        return False
 
     try:
         subprocess.run(
@@ -253,10 +263,12 @@ def _create_and_merge_pr(branch: str, target: str) -> None:
             check=True,
         )
         print(f"  PR merged into {target}")
+        return True
     except FileNotFoundError:
         print("  Warning: 'gh' CLI not found. Cannot merge PR.", file=sys.stderr)
     except subprocess.CalledProcessError as e:
         print(f"  Warning: Failed to merge PR: {e.stderr.strip()}", file=sys.stderr)
+    return False
 
 
 def create_branch_config(
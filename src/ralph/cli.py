@@ -3,32 +3,39 @@
 from __future__ import annotations
 
 import json
+import logging
 import os
+import re
 import shlex
 import shutil
 import sys
+import tempfile
 import time
+import tomllib
 from datetime import datetime
 from pathlib import Path
-from typing import TYPE_CHECKING
+from typing import TYPE_CHECKING, Any
 
 import click
 from click.shell_completion import CompletionItem
 
 from ralph import __version__
-from ralph.agents import VALID_AGENTS
+from ralph.agents import COMPLETION_SIGNAL, VALID_AGENTS
 from ralph.attach import attach
 from ralph.install_skills import get_skills_target_dir, install_skills
-from ralph.loop import LoopConfig, LoopRunner
+from ralph.loop import DEFAULT_DELAY_SECONDS, DEFAULT_POLL_MS, LoopConfig, LoopRunner
 from ralph.opencode_server import (
     DEFAULT_SERVER_PORT,
     OpencodeClient,
     OpencodeServerNotRunning,
 )
+from ralph.prd import DEFAULT_PRD_NAME, leaf_stories, new_prd, parse_prd_type
 from ralph.session import (
+    UNLIMITED_ITERATIONS,
     SessionDB,
     SessionInfo,
     checkpoint_session,
+    format_max_iterations,
     get_status,
     stop_session,
     task_name_from_dir,
@@ -49,6 +56,53 @@ if TYPE_CHECKING:
     from click import Context, Parameter
 
 DEFAULT_ITERATIONS = 10
+LARGE_ITERATIONS_WARNING = 500  # Warn above this; likely a typo or runaway cost
+
+# --log-level choices for the ~/.local/state/ralph/*.log files
+LOG_LEVELS = {
+    "off": logging.CRITICAL + 1,
+    "error": logging.ERROR,
+    "info": logging.INFO,
+    "debug": logging.DEBUG,
+}
+DEFAULT_LOG_LEVEL = "debug"
+
+
+class IterationsType(click.ParamType):
+    """Max iterations: a positive integer, or "unlimited"/"inf"."""
+
+    name = "iterations"
+
+    def convert(self, value: Any, param: Parameter | None, ctx: Context | None) -> int:
+        if isinstance(value, int):
+            count = value
+        elif str(value).strip().lower() in ("unlimited", "inf"):
+            return UNLIMITED_ITERATIONS
+        else:
+            try:
+                count = int(value)
+            except ValueError:
+                self.fail(f"'{value}' is not a number or 'unlimited'.", param, ctx)
+        if count < 1:
+            self.fail(
+                "must be at least 1 (use 'unlimited' to run until complete).",
+                param,
+                ctx,
+            )
+        return count
+
+
+class UserPath(click.Path):
+    """click.Path that expands a leading ~ before validating.
+
+    The shell only expands ~ at the start of a word, so values given as
+    --option=~/dir, or read from config.toml, would otherwise arrive as-is.
+    """
+
+    def convert(self, value: Any, param: Parameter | None, ctx: Context | None) -> Any:
+        if isinstance(value, str):
+            value = os.path.expanduser(value)
+        return super().convert(value, param, ctx)
 
 
 def _ensure_skills_installed() -> None:
@@ -101,20 +155,28 @@ def _get_git_root(from_dir: Path | None = None) -> Path | None:
         return None
 
 
-def _resolve_task_dir(task_input: str) -> Path | None:
+def _resolve_task_dir(
+    task_input: str, tasks_dir: Path = Path("tasks"), prd_name: str = DEFAULT_PRD_NAME
+) -> Path | None:
     """Resolve a task directory from user input.
 
     Supports:
     - Full path: tasks/my-feature or /abs/path/tasks/my-feature
     - Task name only: my-feature (resolves to tasks/my-feature at git root)
 
+    Args:
+        task_input: Path or task name given on the command line.
+        tasks_dir: Directory task names are looked up in (relative paths are
+            taken from the git root).
+        prd_name: File name a task directory must contain (--prd-file).
+
     Returns:
         Resolved Path if found, None otherwise.
     """
     input_path = Path(task_input)
 
-    # If it's already a valid directory with prd.json, use it
-    if input_path.is_dir() and (input_path / "prd.json").is_file():
+    # If it's already a valid directory with a PRD, use it
+    if input_path.is_dir() and (input_path / prd_name).is_file():
         return input_path.resolve()
 
     # Try resolving as absolute path
@@ -123,47 +185,102 @@ def _resolve_task_dir(task_input: str) -> Path | None:
 
     # Try as relative path from cwd
     cwd_path = Path.cwd() / input_path
-    if cwd_path.is_dir() and (cwd_path / "prd.json").is_file():
+    if cwd_path.is_dir() and (cwd_path / prd_name).is_file():
         return cwd_path.resolve()
 
-    # Try as task name under tasks/ at git root
+    # Try as task name under the tasks directory (tasks/ at git root by default)
+    if tasks_dir.is_absolute():
+        task_path = tasks_dir / task_input
+        if task_path.is_dir() and (task_path / prd_name).is_file():
+            return task_path.resolve()
+        return None
+
     git_root = _get_git_root()
     if git_root:
-        task_path = git_root / "tasks" / task_input
-        if task_path.is_dir() and (task_path / "prd.json").is_file():
+        task_path = git_root / tasks_dir / task_input
+        if task_path.is_dir() and (task_path / prd_name).is_file():
             return task_path.resolve()
 
     return None
 
 
-def _find_active_tasks() -> list[Path]:
-    """Find active task directories (those with prd.json, excluding archived)."""
-    tasks_dir = Path("tasks")
+def _find_active_tasks(
+    tasks_dir: Path = Path("tasks"),
+    include_archived: bool = False,
+    prd_name: str = DEFAULT_PRD_NAME,
+) -> list[Path]:
+    """Find active task directories (those with prd.json, excluding archived).
+
+    Args:
+        tasks_dir: Directory searched recursively for PRD files.
+        include_archived: Also return tasks under an archived/ directory.
+        prd_name: PRD file name to look for (--prd-file).
+    """
     if not tasks_dir.is_dir():
         return []
 
     results: list[Path] = []
-    for prd_file in sorted(tasks_dir.rglob("prd.json")):
-        if "archived" in prd_file.parts:
+    for prd_file in sorted(tasks_dir.rglob(prd_name)):
+        relative_parts = prd_file.relative_to(tasks_dir).parts
+        if not include_archived and "archived" in relative_parts:
             continue
         results.append(prd_file.parent)
 
     return results
 
 
-def _display_task_info(task_dir: Path) -> str:
+def _offer_prd_scaffold(
+    task_input: str, tasks_dir: Path, prd_name: str = DEFAULT_PRD_NAME
+) -> Path | None:
+    """Offer to write a starter PRD into an existing task directory.
+
+    Only applies when the directory exists but has no PRD; unknown task
+    names still get the "Task not found" error.
+
+    Returns:
+        The resolved task directory if a PRD was written, None otherwise.
+    """
+    git_root = _get_git_root()
+    candidates = [Path(task_input)]
+    if git_root:
+        candidates.append(git_root / tasks_dir / task_input)
+    task_path = next((c for c in candidates if c.is_dir()), None)
+    if task_path is None:
+        return None
+
+    task_path = task_path.resolve()
+    prd_file = task_path / prd_name
+    if not click.confirm(f"No {prd_name} in {task_path}. Create a starter one?"):
+        return None
+
+    project_root = git_root or task_path.parent.parent
+    try:
+        rel_dir = task_path.relative_to(project_root).as_posix()
+    except ValueError:
+        rel_dir = str(task_path)
+    project = click.prompt("Project name", default=project_root.name)
+    description = click.prompt("Description")
+    story_title = click.prompt("First story title")
+
+    prd = new_prd(project, rel_dir, description, story_title)
+    prd_file.write_text(json.dumps(prd, indent=2) + "\n")
+    click.echo(f"Wrote {prd_file}. Add stories and acceptance criteria as needed.")
+    return task_path
+
+
+def _display_task_info(task_dir: Path, prd_name: str = DEFAULT_PRD_NAME) -> str:
     """Format a task directory for display."""
-    prd_file = task_dir / "prd.json"
+    prd_file = task_dir / prd_name
     total = "?"
     done = "?"
     prd_type = "feature"
 
     try:
         prd = json.loads(prd_file.read_text())
-        stories = prd.get("userStories", [])
+        stories = leaf_stories(prd)
         total = str(len(stories))
         done = str(sum(1 for s in stories if s.get("passes", False)))
-        prd_type = str(prd.get("type", "feature"))
+        prd_type = str(parse_prd_type(prd))
     except (json.JSONDecodeError, OSError):
         pass
 
@@ -175,15 +292,19 @@ def _detect_installed_agents() -> list[str]:
     return [agent for agent in VALID_AGENTS if shutil.which(agent) is not None]
 
 
-def _prompt_task_selection() -> Path | None:
+def _prompt_task_selection(
+    tasks_dir: Path = Path("tasks"),
+    include_archived: bool = False,
+    prd_name: str = DEFAULT_PRD_NAME,
+) -> Path | None:
     """Interactively prompt the user to select a task directory."""
-    tasks = _find_active_tasks()
+    tasks = _find_active_tasks(tasks_dir, include_archived, prd_name)
 
     if not tasks:
-        if not Path("tasks").is_dir():
-            click.echo("No tasks/ directory found in current project.")
+        if not tasks_dir.is_dir():
+            click.echo(f"No {tasks_dir}/ directory found in current project.")
         else:
-            click.echo("No active tasks found in tasks/.")
+            click.echo(f"No active tasks found in {tasks_dir}/.")
         click.echo()
         click.echo("To create a new task:")
         click.echo("  1. Use /prd in Claude Code to create a PRD")
@@ -203,7 +324,7 @@ def _prompt_task_selection() -> Path | None:
     click.echo()
 
     for i, task in enumerate(tasks, 1):
-        click.echo(f"  {i}) {_display_task_info(task)}")
+        click.echo(f"  {i}) {_display_task_info(task, prd_name)}")
 
     click.echo()
     selection: int = click.prompt(f"Select task [1-{len(tasks)}]", type=int, default=1)
@@ -220,6 +341,7 @@ def _resolve_agent(
     cli_agent: str | None,
     task_dir: Path,
     skip_prompts: bool,
+    prd_name: str = DEFAULT_PRD_NAME,
 ) -> str:
     """Resolve which agent to use.
 
@@ -232,7 +354,7 @@ def _resolve_agent(
         return cli_agent
 
     # 2. Check prd.json for saved agent
-    prd_file = task_dir / "prd.json"
+    prd_file = task_dir / prd_name
     if prd_file.is_file():
         try:
             prd = json.loads(prd_file.read_text())
@@ -344,18 +466,21 @@ def _complete_task_dirs(
 ) -> list[CompletionItem]:
     """Complete task directory paths.
 
-    Suggests directories under tasks/ that contain prd.json.
+    Suggests directories under tasks/ (or --tasks-dir) that contain prd.json.
     """
-    tasks = _find_active_tasks()
+    prd_name = ctx.params.get("prd_name") or DEFAULT_PRD_NAME
+    tasks = _find_active_tasks(
+        Path(ctx.params.get("tasks_dir") or "tasks"), prd_name=prd_name
+    )
     completions = []
     for task_dir in tasks:
         task_str = str(task_dir)
         if task_str.startswith(incomplete) or incomplete == "":
             # Get description from prd.json for help text
             try:
-                prd = json.loads((task_dir / "prd.json").read_text())
+                prd = json.loads((task_dir / prd_name).read_text())
                 desc = str(prd.get("description", ""))[:40]
-                stories = prd.get("userStories", [])
+                stories = leaf_stories(prd)
                 done = sum(1 for s in stories if s.get("passes", False))
                 help_text = f"[{done}/{len(stories)}] {desc}"
             except (json.JSONDecodeError, OSError):
@@ -422,19 +547,129 @@ def _complete_branch_names(
     return completions
 
 
+def _parse_env_option(
+    ctx: Context, param: Parameter, values: tuple[str, ...]
+) -> dict[str, str]:
+    """Parse repeated --env KEY=VALUE options into a dict."""
+    env: dict[str, str] = {}
+    for item in values:
+        key, sep, value = item.partition("=")
+        if not sep or not re.fullmatch(r"[A-Za-z_][A-Za-z0-9_]*", key):
+            raise click.BadParameter(f"expected KEY=VALUE, got '{item}'")
+        env[key] = value
+    return env
+
+
+def _validate_complete_marker(ctx: Context, param: Parameter, value: str) -> str:
+    """Reject a --complete-marker that would match any output."""
+    if not value.strip():
+        raise click.BadParameter("must not be empty")
+    return value
+
+
+def _validate_notify_url(
+    ctx: Context, param: Parameter, value: str | None
+) -> str | None:
+    """Reject --notify-url values urllib cannot POST to."""
+    if value and not value.startswith(("http://", "https://")):
+        raise click.BadParameter("must be an http:// or https:// URL")
+    return value
+
+
+# run() options never put on a spawned loop's command line: -y replaces the
+# prompts, and --env and --notify-url go in a private file (see
+# _write_secrets_file)
+_NOT_FORWARDED = frozenset({"skip_prompts", "foreground", "agent_env", "notify_url"})
+
+
+def _inner_run_command(task_dir: Path, **resolved: Any) -> list[str]:
+    """Build the `ralph run` command line for a spawned loop.
+
+    Every option of the current `ralph run` is forwarded with its resolved
+    value, defaults included. The inner process skips config.toml and an
+    explicit flag beats any RALPH_* variable it inherits, so it runs with
+    exactly what the user asked for.
+
+    Args:
+        task_dir: Resolved task directory.
+        **resolved: Values run() has already worked out (agent, iterations,
+            absolute paths), overriding the raw option values.
+
+    Returns:
+        The command, starting with the Python interpreter.
+    """
+    ctx = click.get_current_context()
+    cmd_parts = [sys.executable, "-m", "ralph.cli", "run", str(task_dir), "-y"]
+    for param in ctx.command.params:
+        if not isinstance(param, click.Option) or param.name in _NOT_FORWARDED:
+            continue
+        value = resolved.get(str(param.name), ctx.params.get(str(param.name)))
+        flag = max(param.opts, key=len)  # Long form
+        if param.is_flag:
+            if value:
+                cmd_parts.append(flag)
+        elif value is not None:
+            cmd_parts.extend([flag, str(value)])
+    return cmd_parts
+
+
+# Names the private file a spawned loop reads its secret options from
+SECRETS_FILE_ENV = "RALPH_SECRETS_FILE"
+
+
+def _write_secrets_file(
+    agent_env: dict[str, str], notify_url: str | None
+) -> Path | None:
+    """Hand a spawned loop its secret options through a private file.
+
+    --env values (API keys) and --notify-url (webhook tokens) would show up
+    in ps on any command line, including the one tmux runs. They go in a
+    mode-0600 temp file instead; only its path is passed on, in
+    RALPH_SECRETS_FILE, and the inner ralph deletes it once read (see
+    _read_secrets_file).
+
+    Returns:
+        The file path, or None when there is nothing to hand over.
+    """
+    if not agent_env and not notify_url:
+        return None
+    fd, path = tempfile.mkstemp(prefix="ralph-secrets-", suffix=".json")
+    with os.fdopen(fd, "w") as fh:
+        json.dump({"env": agent_env, "notify_url": notify_url}, fh)
+    return Path(path)
+
+
+def _read_secrets_file(path: str) -> tuple[dict[str, str], str | None]:
+    """Load and delete the file written by _write_secrets_file.
+
+    Returns:
+        The --env values for the agent and the --notify-url value.
+    """
+    secrets_path = Path(path)
+    try:
+        secrets = json.loads(secrets_path.read_text())
+    except (OSError, ValueError) as e:
+        click.echo(f"Error: Could not read secrets file {path}: {e}", err=True)
+        raise SystemExit(1) from e
+    finally:
+        secrets_path.unlink(missing_ok=True)
+    return dict(secrets.get("env", {})), secrets.get("notify_url")
+
+
 def _spawn_in_tmux(
     task_dir: Path,
     max_iterations: int,
     agent: str,
-    base_branch: str | None,
-    yolo: bool,
-    verbose: bool,
-    model: str | None,
+    cmd_parts: list[str],
+    agent_env: dict[str, str],
+    notify_url: str | None,
 ) -> int:
     """Spawn ralph inside a tmux session.
 
-    Creates a detached tmux session running ralph with the same arguments
-    plus RALPH_TMUX_SESSION set. Registers the session in SQLite.
+    Creates a detached tmux session running cmd_parts (see
+    _inner_run_command) with RALPH_TMUX_SESSION set. --env and
+    --notify-url are handed over in a secrets file (see
+    _write_secrets_file). Registers the session in SQLite.
     Returns 0 on success.
     """
     task_name = task_name_from_dir(task_dir)
@@ -457,27 +692,10 @@ def _spawn_in_tmux(
         # DB says running but tmux is gone — stale entry, clean it up
         db.update_status(task_name, "failed")
 
-    # Build command to run inside tmux
-    cmd_parts: list[str] = [
-        sys.executable,
-        "-m",
-        "ralph.cli",
-        "run",
-        str(task_dir),
-        "-i",
-        str(max_iterations),
-        "-a",
-        agent,
-        "-y",  # Skip prompts inside tmux
-    ]
-    if base_branch:
-        cmd_parts.extend(["--base-branch", base_branch])
-    if yolo:
-        cmd_parts.append("--yolo")
-    if verbose:
-        cmd_parts.append("--verbose")
-    if model:
-        cmd_parts.extend(["--model", model])
+    environment = {"RALPH_TMUX_SESSION": session_name}
+    secrets_file = _write_secrets_file(agent_env, notify_url)
+    if secrets_file:
+        environment[SECRETS_FILE_ENV] = str(secrets_file)
 
     # Create tmux session via libtmux
     cmd_str = shlex.join(cmd_parts)
@@ -486,13 +704,15 @@ def _spawn_in_tmux(
         session_name,
         cmd_str,
         project_root,
-        environment={"RALPH_TMUX_SESSION": session_name},
+        environment=environment,
     )
 
     # Give the inner process a moment to start, then verify it survived
     time.sleep(1.0)
 
     if not tmux_session_alive(session_name):
+        if secrets_file:
+            secrets_file.unlink(missing_ok=True)
         # Capture any crash output from the pane before killing
         if tmux_session_exists(session_name):
             tmux_kill_session(session_name)
@@ -504,7 +724,12 @@ def _spawn_in_tmux(
             "  The inner process likely crashed. Try running directly:",
             err=True,
         )
-        click.echo(f"  RALPH_TMUX_SESSION={session_name} {cmd_str}", err=True)
+        hidden = "".join(f" --env {key}=..." for key in agent_env)
+        if notify_url:
+            hidden += " --notify-url ..."
+        click.echo(
+            f"  RALPH_TMUX_SESSION={session_name} {cmd_str}{hidden}", err=True
+        )
         return 1
 
     now = datetime.now().isoformat()
@@ -539,6 +764,17 @@ def _run_opencode_worker(
     workspace_name: str | None = None,
     workspace_reset: bool = False,
     workspace_keep: bool = False,
+    event_log: Path | None = None,
+    prompt_file: Path | None = None,
+    stall_limit: int = 0,
+    summary_json: bool = False,
+    delay_seconds: float = DEFAULT_DELAY_SECONDS,
+    poll_ms: int = DEFAULT_POLL_MS,
+    allow_merge: bool = False,
+    prd_name: str = DEFAULT_PRD_NAME,
+    notify_url: str | None = None,
+    notify_on: str = "complete",
+    incomplete_only: bool = False,
 ) -> int:
     """Run the opencode loop directly (worker process or foreground mode).
 
@@ -666,6 +902,17 @@ def _run_opencode_worker(
         model=model,
         workspace_dir=workspace_dir,
         workspace_keep=workspace_keep,
+        event_log=event_log,
+        prompt_file=prompt_file,
+        stall_limit=stall_limit,
+        summary_json=summary_json,
+        delay_seconds=delay_seconds,
+        poll_ms=poll_ms,
+        allow_merge=allow_merge,
+        prd_name=prd_name,
+        notify_url=notify_url,
+        notify_on=notify_on,
+        incomplete_only=incomplete_only,
     )
     # skip_session_register: already registered above with correct session_type
     runner = LoopRunner(config, opencode_server=client, skip_session_register=True)
@@ -689,20 +936,15 @@ def _run_opencode_worker(
 
 def _spawn_opencode_background(
     task_dir: Path,
-    max_iterations: int,
-    agent: str,
-    base_branch: str | None,
-    yolo: bool,
-    verbose: bool,
-    model: str | None,
-    workspace_name: str | None = None,
-    workspace_reset: bool = False,
-    workspace_keep: bool = False,
+    cmd_parts: list[str],
+    notify_url: str | None,
 ) -> int:
     """Spawn a detached background worker for opencode mode.
 
-    The parent process exits immediately after spawning the worker.
-    The worker is immune to terminal close (SIGHUP).
+    Runs cmd_parts (see _inner_run_command) with RALPH_WORKER set, handing
+    --notify-url over in a secrets file (see _write_secrets_file). The
+    parent process exits immediately after spawning the worker. The worker
+    is immune to terminal close (SIGHUP).
     Returns 0 on success (worker started).
     """
     task_name = task_name_from_dir(task_dir)
@@ -731,38 +973,6 @@ def _spawn_opencode_background(
         # Stale entry — clean it up
         db.update_status(task_name, "failed")
 
-    # Build command to run inside the worker
-    cmd_parts: list[str] = [
-        sys.executable,
-        "-m",
-        "ralph.cli",
-        "run",
-        str(task_dir),
-        "-i",
-        str(max_iterations),
-        "-a",
-        agent,
-        "-y",  # Skip prompts in worker
-    ]
-    if base_branch:
-        cmd_parts.extend(["--base-branch", base_branch])
-    if yolo:
-        cmd_parts.append("--yolo")
-    if verbose:
-        cmd_parts.append("--verbose")
-    if model:
-        cmd_parts.extend(["--model", model])
-    # Pass workspace options to worker
-    # workspace_name: "" = auto-generate, "name" = specific name, None = no workspace
-    if workspace_name is not None:
-        cmd_parts.append("--workspace")
-        if workspace_name:  # Non-empty = specific name
-            cmd_parts.extend(["--workspace-name", workspace_name])
-    if workspace_reset:
-        cmd_parts.append("--workspace-reset")
-    if workspace_keep:
-        cmd_parts.append("--workspace-keep")
-
     # Set up log files for stdout/stderr
     log_dir = Path.home() / ".local" / "state" / "ralph"
     log_dir.mkdir(parents=True, exist_ok=True)
@@ -771,8 +981,10 @@ def _spawn_opencode_background(
     # Spawn detached worker process
     import subprocess
 
-    env = os.environ.copy()
-    env["RALPH_WORKER"] = "1"
+    env = {**os.environ, "RALPH_WORKER": "1"}
+    secrets_file = _write_secrets_file({}, notify_url)
+    if secrets_file:
+        env[SECRETS_FILE_ENV] = str(secrets_file)
 
     with open(log_file, "w") as log_fh:
         proc = subprocess.Popen(
@@ -789,6 +1001,8 @@ def _spawn_opencode_background(
     time.sleep(1.0)
 
     if proc.poll() is not None:
+        if secrets_file:
+            secrets_file.unlink(missing_ok=True)
         # Worker died immediately - show the user what went wrong
         click.echo()
         click.echo(
@@ -827,27 +1041,81 @@ def _spawn_opencode_background(
 # --- Click CLI ---
 
 
+def _load_config_defaults(config_file: Path) -> dict[str, dict[str, Any]]:
+    """Read option defaults from the user config file.
+
+    Each table is a command name and each key a long option name without
+    the dashes, e.g. `[run]` with `max-iterations = 20` or `delay = 5`. The
+    result is a click default_map, so flags and env vars still take
+    precedence over the file. Problems are reported and skipped.
+    """
+    if not config_file.is_file():
+        return {}
+    try:
+        data = tomllib.loads(config_file.read_text())
+    except (tomllib.TOMLDecodeError, OSError) as e:
+        click.echo(f"Warning: Ignoring {config_file}: {e}", err=True)
+        return {}
+
+    defaults: dict[str, dict[str, Any]] = {}
+    for command_name, table in data.items():
+        command = cli.commands.get(command_name)
+        if command is None or not isinstance(table, dict):
+            click.echo(
+                f"Warning: {config_file}: unknown section [{command_name}]", err=True
+            )
+            continue
+        names = {
+            opt.lstrip("-").replace("-", "_"): param.name
+            for param in command.params
+            for opt in param.opts
+            if opt.startswith("--")
+        }
+        for key, value in table.items():
+            name = names.get(key.replace("-", "_"))
+            if name is None:
+                click.echo(
+                    f"Warning: {config_file}: unknown option "
+                    f"'{key}' in [{command_name}]",
+                    err=True,
+                )
+                continue
+            defaults.setdefault(command_name, {})[name] = value
+    return defaults
+
+
 @click.group()
 @click.version_option(version=__version__, prog_name="ralph")
-def cli() -> None:
+@click.pass_context
+def cli(ctx: Context) -> None:
     """Ralph - Autonomous AI agent loop runner."""
+    # The file logs record everything unless `run --log-level` says otherwise
+    logging.getLogger("ralph").setLevel(LOG_LEVELS[DEFAULT_LOG_LEVEL])
     # Auto-install skills on first run
     _ensure_skills_installed()
+    defaults = _load_config_defaults(Path.home() / ".config" / "ralph" / "config.toml")
+    if os.environ.get("RALPH_TMUX_SESSION") or os.environ.get("RALPH_WORKER"):
+        # A spawned loop gets every option from its parent (see
+        # _inner_run_command); config.toml must not override them
+        defaults.pop("run", None)
+    ctx.default_map = defaults
 
 
 @cli.command()
 @click.argument(
     "task_dir",
     required=False,
-    type=click.Path(exists=False),
+    type=UserPath(exists=False),
     shell_complete=_complete_task_dirs,
 )
 @click.option(
     "-i",
     "--max-iterations",
-    type=int,
+    type=IterationsType(),
     default=None,
-    help=f"Maximum iterations (default: {DEFAULT_ITERATIONS}).",
+    envvar="RALPH_MAX_ITERATIONS",
+    help=f"Maximum iterations, or 'unlimited' (default: {DEFAULT_ITERATIONS}; "
+    "env: RALPH_MAX_ITERATIONS).",
 )
 @click.option(
     "-a",
@@ -902,6 +1170,134 @@ def cli() -> None:
     is_flag=True,
     help="Keep workspace after completion (for debugging).",
 )
+@click.option(
+    "--event-log",
+    type=UserPath(dir_okay=False),
+    default=None,
+    help="Append JSON-lines loop events to this file.",
+)
+@click.option(
+    "--prompt",
+    "prompt_file",
+    type=UserPath(exists=True, dir_okay=False),
+    default=None,
+    help="Prompt template to use instead of the prompt.md search path.",
+)
+@click.option(
+    "--stall-limit",
+    type=click.IntRange(min=0),
+    default=0,
+    help="Stop after N iterations in a row with no story or criterion passing "
+    "(default: 0, never).",
+)
+@click.option(
+    "--summary-json",
+    is_flag=True,
+    help="Print the end-of-run summary as a JSON object.",
+)
+@click.option(
+    "--settings",
+    "settings_file",
+    type=UserPath(exists=True, dir_okay=False),
+    default=None,
+    help="Settings file passed to claude --settings (e.g. custom hooks).",
+)
+@click.option(
+    "--env",
+    "agent_env",
+    multiple=True,
+    callback=_parse_env_option,
+    metavar="KEY=VALUE",
+    help="Set an environment variable for the agent process (repeatable).",
+)
+@click.option(
+    "--capture-dir",
+    type=UserPath(file_okay=False),
+    default=None,
+    help="Save each iteration's raw agent output to DIR/iteration-N.log.",
+)
+@click.option(
+    "--delay",
+    "delay_seconds",
+    type=click.FloatRange(min=0),
+    default=DEFAULT_DELAY_SECONDS,
+    envvar="RALPH_DELAY_SECS",
+    help=f"Seconds to wait between iterations (default: {DEFAULT_DELAY_SECONDS:g}; "
+    "env: RALPH_DELAY_SECS).",
+)
+@click.option(
+    "--tasks-dir",
+    type=UserPath(file_okay=False),
+    default="tasks",
+    help="Directory searched for task prd.json files (default: tasks).",
+)
+@click.option(
+    "--include-archived",
+    is_flag=True,
+    help="Also offer tasks under archived/ directories.",
+)
+@click.option(
+    "--poll-ms",
+    type=click.IntRange(min=50),
+    default=DEFAULT_POLL_MS,
+    help="How often to poll the opencode server for session status, in "
+    f"milliseconds (default: {DEFAULT_POLL_MS}; opencode agent only).",
+)
+@click.option(
+    "--allow-merge",
+    is_flag=True,
+    help="Let ralph merge the completion PR when prd.json sets autoMerge.",
+)
+@click.option(
+    "--idle-timeout",
+    type=click.FloatRange(min=0),
+    default=0,
+    metavar="SECONDS",
+    help="Stop an iteration when the agent prints nothing for this long "
+    "(default: 0, never).",
+)
+@click.option(
+    "--prd-file",
+    "prd_name",
+    default=DEFAULT_PRD_NAME,
+    metavar="NAME",
+    help=f"PRD file name inside the task directory (default: {DEFAULT_PRD_NAME}).",
+)
+@click.option(
+    "--complete-marker",
+    "completion_signal",
+    default=COMPLETION_SIGNAL,
+    callback=_validate_complete_marker,
+    metavar="TEXT",
+    help=f"Agent output that means all work is done (default: {COMPLETION_SIGNAL}).",
+)
+@click.option(
+    "--notify-url",
+    callback=_validate_notify_url,
+    envvar="RALPH_NOTIFY_URL",
+    metavar="URL",
+    help="POST a JSON run summary to this URL when the run ends "
+    "(env: RALPH_NOTIFY_URL).",
+)
+@click.option(
+    "--notify-on",
+    type=click.Choice(["complete", "always"]),
+    default="complete",
+    help="When to send --notify-url: only on completion, or on every exit "
+    "(default: complete).",
+)
+@click.option(
+    "--incomplete-only",
+    is_flag=True,
+    help="List only the remaining stories in the agent prompt.",
+)
+@click.option(
+    "--log-level",
+    type=click.Choice(list(LOG_LEVELS)),
+    default=DEFAULT_LOG_LEVEL,
+    help="Detail written to the agent, server and workspace logs "
+    "(default: debug).",
+)
 def run(
     task_dir: str | None,
     max_iterations: int | None,
@@ -916,24 +1312,57 @@ def run(
     workspace_name: str | None,
     workspace_reset: bool,
     workspace_keep: bool,
+    event_log: str | None,
+    prompt_file: str | None,
+    stall_limit: int,
+    summary_json: bool,
+    settings_file: str | None,
+    agent_env: dict[str, str],
+    capture_dir: str | None,
+    delay_seconds: float,
+    tasks_dir: str,
+    include_archived: bool,
+    poll_ms: int,
+    allow_merge: bool,
+    idle_timeout: float,
+    completion_signal: str,
+    prd_name: str,
+    notify_url: str | None,
+    notify_on: str,
+    incomplete_only: bool,
+    log_level: str,
 ) -> None:
     """Run the agent loop for a task."""
+    logging.getLogger("ralph").setLevel(LOG_LEVELS[log_level])
+    # Read by click already; don't leak the webhook token to agent processes
+    os.environ.pop("RALPH_NOTIFY_URL", None)
+    secrets_file = os.environ.pop(SECRETS_FILE_ENV, None)
+    if secrets_file:
+        # A spawned loop: secret options came in a private file, not on argv
+        agent_env, notify_url = _read_secrets_file(secrets_file)
+
     # --- Resolve task directory ---
+    tasks_root = Path(tasks_dir)
+    if Path(prd_name).name != prd_name:
+        click.echo("Error: --prd-file takes a file name, not a path.", err=True)
+        raise SystemExit(1)
     if task_dir:
         # Try smart resolution: full path, relative path, or task name
-        resolved_dir = _resolve_task_dir(task_dir)
+        resolved_dir = _resolve_task_dir(task_dir, tasks_root, prd_name)
+        if resolved_dir is None and not skip_prompts:
+            resolved_dir = _offer_prd_scaffold(task_dir, tasks_root, prd_name)
         if resolved_dir is None:
             # Provide helpful error message
             git_root = _get_git_root()
             if git_root:
-                tasks_path = git_root / "tasks" / task_dir
+                tasks_path = git_root / tasks_root / task_dir
                 click.echo(
                     f"Error: Task not found: '{task_dir}'\n"
                     f"  Looked in: {tasks_path}\n"
                     f"  Available tasks:",
                     err=True,
                 )
-                for t in _find_active_tasks():
+                for t in _find_active_tasks(tasks_root, include_archived, prd_name):
                     click.echo(f"    - {t.name}", err=True)
             else:
                 click.echo(f"Error: Task directory not found: {task_dir}", err=True)
@@ -942,7 +1371,7 @@ def run(
         click.echo("Error: task_dir is required with --yes flag.", err=True)
         raise SystemExit(1)
     else:
-        selected = _prompt_task_selection()
+        selected = _prompt_task_selection(tasks_root, include_archived, prd_name)
         if selected is None:
             raise SystemExit(1)
         resolved_dir = selected.resolve()
@@ -953,13 +1382,19 @@ def run(
             max_iterations = DEFAULT_ITERATIONS
         else:
             max_iterations = click.prompt(
-                "Max iterations", type=int, default=DEFAULT_ITERATIONS
+                "Max iterations", type=IterationsType(), default=DEFAULT_ITERATIONS
             )
 
     assert max_iterations is not None  # Guaranteed by prompt/default above
+    if LARGE_ITERATIONS_WARNING < max_iterations < UNLIMITED_ITERATIONS:
+        click.echo(
+            f"Warning: {max_iterations} iterations is unusually high. "
+            f"Use '-i unlimited' if you mean to run until complete.",
+            err=True,
+        )
 
     # --- Resolve agent ---
-    resolved_agent = _resolve_agent(agent, resolved_dir, skip_prompts)
+    resolved_agent = _resolve_agent(agent, resolved_dir, skip_prompts, prd_name)
 
     # --- Normalize workspace options ---
     # workspace_name implies workspace=True
@@ -983,6 +1418,40 @@ def run(
         )
         raise SystemExit(1)
 
+    # --- Validate claude-only options ---
+    # opencode runs go through the shared server, which we don't spawn
+    claude_only = {
+        "--settings": settings_file,
+        "--env": agent_env,
+        "--capture-dir": capture_dir,
+        "--idle-timeout": idle_timeout,
+        "--complete-marker": completion_signal != COMPLETION_SIGNAL,
+    }
+    for flag, value in claude_only.items():
+        if value and resolved_agent != "claude":
+            click.echo(
+                f"Error: {flag} is only supported with the claude agent.",
+                err=True,
+            )
+            raise SystemExit(1)
+
+    # Resolve now: the inner process runs from the project root, not our cwd
+    event_log_path = Path(event_log).resolve() if event_log else None
+    prompt_path = Path(prompt_file).resolve() if prompt_file else None
+    settings_path = Path(settings_file).resolve() if settings_file else None
+    capture_path = Path(capture_dir).resolve() if capture_dir else None
+
+    # What a spawned inner loop (tmux or background worker) is started with
+    inner_cmd = _inner_run_command(
+        resolved_dir,
+        max_iterations=format_max_iterations(max_iterations),
+        agent=resolved_agent,
+        event_log=event_log_path,
+        prompt_file=prompt_path,
+        settings_file=settings_path,
+        capture_dir=capture_path,
+    )
+
     # --- Check if we're inside tmux or a worker process already ---
     running_in_tmux = os.environ.get("RALPH_TMUX_SESSION", "")
     running_as_worker = os.environ.get("RALPH_WORKER", "")
@@ -998,6 +1467,21 @@ def run(
             yolo_mode=yolo,
             verbose=verbose,
             model=model,
+            event_log=event_log_path,
+            prompt_file=prompt_path,
+            stall_limit=stall_limit,
+            summary_json=summary_json,
+            settings_file=settings_path,
+            agent_env=agent_env,
+            capture_dir=capture_path,
+            delay_seconds=delay_seconds,
+            allow_merge=allow_merge,
+            idle_timeout=idle_timeout,
+            completion_signal=completion_signal,
+            prd_name=prd_name,
+            notify_url=notify_url,
+            notify_on=notify_on,
+            incomplete_only=incomplete_only,
         )
         runner = LoopRunner(config)
         raise SystemExit(runner.run())
@@ -1014,6 +1498,17 @@ def run(
             workspace_name=effective_workspace_name,
             workspace_reset=workspace_reset,
             workspace_keep=workspace_keep,
+            event_log=event_log_path,
+            prompt_file=prompt_path,
+            stall_limit=stall_limit,
+            summary_json=summary_json,
+            delay_seconds=delay_seconds,
+            poll_ms=poll_ms,
+            allow_merge=allow_merge,
+            prd_name=prd_name,
+            notify_url=notify_url,
+            notify_on=notify_on,
+            incomplete_only=incomplete_only,
         )
         raise SystemExit(rc)
     elif resolved_agent == "opencode":
@@ -1030,31 +1525,30 @@ def run(
                 workspace_name=effective_workspace_name,
                 workspace_reset=workspace_reset,
                 workspace_keep=workspace_keep,
+                event_log=event_log_path,
+                prompt_file=prompt_path,
+                stall_limit=stall_limit,
+                summary_json=summary_json,
+                delay_seconds=delay_seconds,
+                poll_ms=poll_ms,
+                allow_merge=allow_merge,
+                prd_name=prd_name,
+                notify_url=notify_url,
+                notify_on=notify_on,
+                incomplete_only=incomplete_only,
             )
         else:
-            rc = _spawn_opencode_background(
-                task_dir=resolved_dir,
-                max_iterations=max_iterations,
-                agent=resolved_agent,
-                base_branch=base_branch,
-                yolo=yolo,
-                verbose=verbose,
-                model=model,
-                workspace_name=effective_workspace_name,
-                workspace_reset=workspace_reset,
-                workspace_keep=workspace_keep,
-            )
+            rc = _spawn_opencode_background(resolved_dir, inner_cmd, notify_url)
         raise SystemExit(rc)
     else:
         # Claude agent: spawn ourselves in a tmux session (no workspace support)
         rc = _spawn_in_tmux(
-            task_dir=resolved_dir,
-            max_iterations=max_iterations,
-            agent=resolved_agent,
-            base_branch=base_branch,
-            yolo=yolo,
-            verbose=verbose,
-            model=model,
+            resolved_dir,
+            max_iterations,
+            resolved_agent,
+            inner_cmd,
+            agent_env,
+            notify_url,
         )
         raise SystemExit(rc)
 
@@ -1093,7 +1587,12 @@ def checkpoint(task: str) -> None:
     default=None,
     help="Specific opencode session ID to attach to.",
 )
-def attach_cmd(task: str | None, session_id: str | None) -> None:
+@click.option(
+    "--read-only",
+    is_flag=True,
+    help="Watch the session without sending keystrokes (tmux sessions only).",
+)
+def attach_cmd(task: str | None, session_id: str | None, read_only: bool) -> None:
     """Attach to a running session.
 
     \b
@@ -1101,8 +1600,9 @@ def attach_cmd(task: str | None, session_id: str | None) -> None:
       ralph attach              # Attach to most recently active session
       ralph attach <task>       # Attach to most recent session for <task>
       ralph attach <task> --session <id>  # Attach to specific session
+      ralph attach <task> --read-only     # Watch without typing into it
     """
-    rc = attach(task, session_id)
+    rc = attach(task, session_id, read_only)
     if rc != 0:
         raise SystemExit(rc)
 
@@ -0,0 +1,48 @@
+"""Machine-readable event log for Ralph loop runs.
+
+Writes one JSON object per line for each significant loop transition
+(iteration start/end, story completion, signals, final outcome) so that
+dashboards and post-run analysis tools can follow a run without parsing
+the human-oriented terminal output.
+"""
+
+from __future__ import annotations
+
+import json
+from datetime import datetime
+from pathlib import Path
+from typing import Any
+
+
+class EventLogger:
+    """Appends JSON-lines events to a log file.
+
+    The file is opened in append mode for every event and flushed before
+    closing, so external readers (e.g. `tail -f`) see events immediately
+    and a crash never leaves a partially buffered line behind.
+    """
+
+    def __init__(self, path: Path) -> None:
+        """Initialize the event logger.
+
+        Args:
+            path: Path to the JSON-lines file. Parent directories are created.
+        """
+        self.path = path
+        self.path.parent.mkdir(parents=True, exist_ok=True)
+
+    def append(self, event: str, **fields: Any) -> None:
+        """Append a single event to the log.
+
+        Args:
+            event: Event type (e.g. "iteration_start", "story_completed").
+            **fields: Additional JSON-serializable fields for the event.
+        """
+        record: dict[str, Any] = {
+            "timestamp": datetime.now().isoformat(),
+            "event": event,
+        }
+        record.update(fields)
+        with open(self.path, "a") as f:
+            f.write(json.dumps(record) + "\n")
+            f.flush()
@@ -8,16 +8,19 @@ import re
 import signal
 import sys
 import time
-from dataclasses import dataclass
+from dataclasses import dataclass, field
 from datetime import datetime
 from pathlib import Path
 from typing import Any
+from urllib.request import Request, urlopen
 
 from ralph.agents import (
+    COMPLETION_SIGNAL,
     VALID_AGENTS,
     AgentConfig,
     AgentResult,
     FailureTracker,
+    classify_error,
     create_agent,
     resolve_agent,
 )
@@ -29,14 +32,27 @@ from ralph.branch import (
     setup_branch,
     verify_on_branch,
 )
+from ralph.events import EventLogger
 from ralph.opencode_server import OpencodeClient, OpencodeServerError
+from ralph.prd import (
+    DEFAULT_PRD_NAME,
+    count_progress,
+    diff_prd,
+    leaf_stories,
+    parse_prd_type,
+    validate_prd,
+)
 from ralph.prompt import (
     PromptContext,
+    PromptError,
     build_prompt,
+    resolve_prompt_path,
 )
 from ralph.session import (
+    UNLIMITED_ITERATIONS,
     SessionDB,
     SessionInfo,
+    format_max_iterations,
     read_signal,
     task_name_from_dir,
     tmux_session_name,
@@ -46,6 +62,67 @@ from ralph.version import SCHEMA_VERSION, check_schema_version
 DEFAULT_MAX_ITERATIONS = 50
 DEFAULT_ROTATE_THRESHOLD = 300
 DEFAULT_FAILOVER_THRESHOLD = 3
+DEFAULT_DELAY_SECONDS = 2.0
+DEFAULT_POLL_MS = 500  # opencode server status polling interval
+
+# An agent that fails faster than this never really started (bad flags,
+# missing auth, ...). Retrying would just burn iterations on the same error.
+STARTUP_FAILURE_SECONDS = 2.0
+
+# Account problems that will fail every iteration until the user fixes them
+FATAL_ERROR_KINDS = ("auth", "credit")
+ERROR_HINTS = {
+    "auth": "Log in again (claude: /login) or check the API key, then rerun.",
+    "credit": "Add credits or switch to an account with quota, then rerun.",
+}
+
+# Backoff after a rate-limited/overloaded iteration, doubled each time in a row
+RATE_LIMIT_BACKOFF_SECONDS = 60
+RATE_LIMIT_BACKOFF_MAX_SECONDS = 900
+
+# prd.json can be caught half-written (an editor or agent truncating it before
+# the new content lands), so a parse error is retried briefly before giving up
+PRD_READ_RETRIES = 3
+PRD_READ_RETRY_SECONDS = 0.2
+
+# --notify-url: a slow or dead webhook must not hold up the end of a run
+NOTIFY_TIMEOUT_SECONDS = 10
+
+# Exit codes for runs that stop with work left, so scripts can tell them
+# apart from errors (1). 3 is reserved for a future time limit.
+EXIT_MAX_ITERATIONS = 2
+EXIT_STALLED = 4
+
+# Stories that must complete in this run before an ETA is shown
+ETA_MIN_COMPLETED = 2
+
+
+def estimate_remaining_seconds(
+    completion_times: list[float], start_time: float, remaining: int
+) -> float | None:
+    """Estimate time left from the average pace of this run.
+
+    Args:
+        completion_times: time.time() of each story completed in this run.
+        start_time: time.time() when the run started.
+        remaining: Number of stories still to complete.
+
+    Returns:
+        Estimated seconds until done, or None with too little history.
+    """
+    if len(completion_times) < ETA_MIN_COMPLETED:
+        return None
+    per_story = (completion_times[-1] - start_time) / len(completion_times)
+    return per_story * remaining
+
+
+def _format_eta(seconds: float) -> str:
+    """Format an ETA coarsely ("<1m", "~14m", "~2h 05m")."""
+    minutes = round(seconds / 60)
+    if minutes < 1:
+        return "<1m"
+    hours, minutes = divmod(minutes, 60)
+    return f"~{hours}h {minutes:02d}m" if hours else f"~{minutes}m"
 
 
 @dataclass
@@ -64,10 +141,26 @@ class LoopConfig:
     model: str | None = None  # CLI --model override
     workspace_dir: Path | None = None  # If set, run in this worktree directory
     workspace_keep: bool = False  # Don't clean up workspace on completion
+    event_log: Path | None = None  # CLI --event-log JSON-lines output path
+    prompt_file: Path | None = None  # CLI --prompt override for prompt.md
+    stall_limit: int = 0  # CLI --stall-limit (0 = never stop for lack of progress)
+    summary_json: bool = False  # CLI --summary-json: print exit summary as JSON
+    settings_file: Path | None = None  # CLI --settings, passed to claude
+    agent_env: dict[str, str] = field(default_factory=dict)  # CLI --env KEY=VALUE
+    capture_dir: Path | None = None  # CLI --capture-dir for iteration-N.log files
+    delay_seconds: float = DEFAULT_DELAY_SECONDS  # CLI --delay between iterations
+    poll_ms: int = DEFAULT_POLL_MS  # CLI --poll-ms for opencode server status
+    allow_merge: bool = False  # CLI --allow-merge: honor prd.json autoMerge
+    idle_timeout: float = 0  # CLI --idle-timeout, claude agent only (0 = off)
+    prd_name: str = DEFAULT_PRD_NAME  # CLI --prd-file
+    completion_signal: str = COMPLETION_SIGNAL  # CLI --complete-marker
+    notify_url: str | None = None  # CLI --notify-url, POSTed the run summary
+    notify_on: str = "complete"  # CLI --notify-on: "complete" or "always"
+    incomplete_only: bool = False  # CLI --incomplete-only: list remaining stories
 
     @property
     def prd_file(self) -> Path:
-        return self.task_dir / "prd.json"
+        return self.task_dir / self.prd_name
 
     @property
     def progress_file(self) -> Path:
@@ -111,9 +204,27 @@ class LoopRunner:
         self._opencode_server = opencode_server
         self._opencode_session_id: str | None = None
         self._skip_session_register = skip_session_register
+        self._prompt_state: tuple[Path | None, float | None] | None = None
+        self._events = EventLogger(config.event_log) if config.event_log else None
+        self._stall_streak = 0  # Consecutive iterations without any progress
+        self._rate_limit_streak = 0  # Consecutive rate-limited iterations
+        self._iterations_run = 0  # Iterations that actually ran an agent
+        self._completion_times: list[float] = []  # When each story completed
+        self._outcome = "failed"  # Set by _finish() on every normal exit path
+        self._merge_result: str | None = None  # Set by _handle_branch_completion()
+        self._schema_warned = False  # Newer-schema warning already shown
+        self._marker_warned = False  # Ignored --complete-marker warning shown
+        self._previous_iteration = ""  # Summary passed to the next prompt
+        self._story_counts = (0, 0)  # (completed, total) at the last prd.json read
+        self._start_time = time.time()
 
     def run(self) -> int:
-        """Run the loop. Returns exit code (0 = complete, 1 = stopped/failed)."""
+        """Run the loop.
+
+        Returns the exit code: 0 = complete, 1 = stopped/failed,
+        EXIT_MAX_ITERATIONS or EXIT_STALLED when work remains.
+        """
+        self._start_time = time.time()
         self._install_signal_handlers()
         if not self._skip_session_register:
             self._register_session()
@@ -127,6 +238,8 @@ class LoopRunner:
             if self._checkpoint_requested:
                 status = "checkpointed"
             self._update_session_status(status)
+            summary = self._print_summary()
+            self._notify(summary)
             return result
         except Exception:
             self._update_session_status("failed")
@@ -144,6 +257,11 @@ class LoopRunner:
         branch_config = self._setup_branch()
 
         self._print_banner()
+        self._log_event(
+            "loop_start",
+            agent=self.current_agent,
+            max_iterations=self._max_iterations_json,
+        )
 
         for i in range(1, self.config.max_iterations + 1):
             self.current_iteration = i
@@ -153,6 +271,7 @@ class LoopRunner:
 
             if self._shutdown_requested:
                 self._handle_shutdown()
+                self._finish("stopped")
                 return 1
 
             # Verify we're on the expected branch (especially important for workspaces)
@@ -167,62 +286,149 @@ class LoopRunner:
             # Get current story info
             prd = self._read_prd()
             next_story = self._get_next_story(prd)
+            if next_story is None and self._count_blocked(prd):
+                self._print_all_blocked(prd)
+                self._finish("blocked")
+                return 1
             if next_story is None:
                 # All stories complete
                 self._print_complete(i)
+                self._finish("complete")
                 self._handle_branch_completion(branch_config)
                 return 0
 
             completed_count = self._count_completed(prd)
-            total_count = len(prd.get("userStories", []))
+            total_count = len(leaf_stories(prd))
 
             # Update session progress
             story_id = str(next_story.get("id", ""))
             self._update_session_progress(i, story_id)
 
             self._print_iteration_header(i, completed_count, total_count, next_story)
+            self._check_prompt_changed()
 
             # Resolve which agent to use for this iteration
             iteration_agent = self._resolve_agent_name(prd, next_story)
+            self._warn_if_marker_ignored(iteration_agent)
+
+            self._log_event("iteration_start", story=story_id, agent=iteration_agent)
+            passing_before = self._passing_story_ids(prd)
+            progress_before = count_progress(prd)
 
             # Run the agent
+            self._iterations_run += 1
             result = self._run_agent(iteration_agent, next_story)
 
+            error_kind = self._classify_failure(result)
+
+            prd_before = prd
+            prd = self._read_prd()
+            changes = diff_prd(prd_before, prd)
+            if changes:
+                print()
+                for change in changes:
+                    print(f"  {self.config.prd_name}: {change}")
+            for completed_id in self._passing_story_ids(prd) - passing_before:
+                self._completion_times.append(time.time())
+                self._log_event("story_completed", story=completed_id)
+            if count_progress(prd) > progress_before:
+                self._stall_streak = 0
+            else:
+                self._stall_streak += 1
+            self._previous_iteration = self._summarize_iteration(
+                i, story_id, iteration_agent, result, changes
+            )
+            self._log_event(
+                "iteration_end",
+                story=story_id,
+                agent=iteration_agent,
+                exit_code=result.exit_code,
+                duration_seconds=round(result.duration_seconds, 1),
+                failed=result.failed,
+                error_kind=error_kind,
+            )
+
             # Handle result
+            if error_kind in FATAL_ERROR_KINDS:
+                self._print_agent_error(iteration_agent, error_kind, result)
+                self.failures.record_failure(iteration_agent, result.error_message)
+                self._log_failure_to_progress(
+                    iteration_agent, story_id, result.error_message, i
+                )
+                self._finish(
+                    "agent_error", error_kind=error_kind, error=result.error_message
+                )
+                return 1
+            if (
+                result.failed
+                and error_kind is None
+                and result.duration_seconds < STARTUP_FAILURE_SECONDS
+            ):
+                self._print_startup_failure(iteration_agent, result)
+                self.failures.record_failure(iteration_agent, result.error_message)
+                self._log_failure_to_progress(
+                    iteration_agent, story_id, result.error_message, i
+                )
+                self._finish("startup_failure", error=result.error_message)
+                return 1
             if result.failed:
                 self._handle_failure(iteration_agent, next_story, result, i)
             else:
                 self.failures.reset(iteration_agent)
 
+            # Stop if the agent keeps spinning without passing anything
+            limit = self.config.stall_limit
+            stalled = limit > 0 and self._stall_streak >= limit
+
+            if error_kind is None:
+                self._rate_limit_streak = 0
+            elif (
+                not self._shutdown_requested
+                and not stalled
+                and i < self.config.max_iterations
+            ):
+                # Only worth waiting if another iteration will run
+                self._wait_for_rate_limit(error_kind)
+
             # Check for completion signal
             if result.completed:
                 self._print_complete(i)
+                self._finish("complete")
                 self._handle_branch_completion(branch_config)
                 return 0
 
+            if stalled:
+                self._print_stalled()
+                self._finish("stalled", streak=self._stall_streak)
+                return EXIT_STALLED
+
             # Check for external signals after iteration
             self._check_signals()
 
             # Check for shutdown after iteration
             if self._shutdown_requested:
                 self._handle_shutdown()
+                self._finish("stopped")
                 return 1
 
             # Brief pause between iterations
-            print(f"\nIteration {i} complete. Continuing in 2 seconds...")
-            time.sleep(2)
+            delay = self.config.delay_seconds
+            print(f"\nIteration {i} complete. Continuing in {delay:g} seconds...")
+            time.sleep(delay)
 
         # Check if all stories completed during the last iteration
         # (agent may have completed work without outputting COMPLETION_SIGNAL)
         prd = self._read_prd()
-        if self._get_next_story(prd) is None:
+        if self._get_next_story(prd) is None and not self._count_blocked(prd):
             self._print_complete(self.config.max_iterations)
+            self._finish("complete")
             self._handle_branch_completion(branch_config)
             return 0
 
         # Max iterations reached with work remaining
         self._print_max_iterations()
-        return 1
+        self._finish("max_iterations")
+        return EXIT_MAX_ITERATIONS
 
     def _register_session(self) -> None:
         """Register this loop in the session database."""
@@ -259,6 +465,7 @@ class LoopRunner:
         if signal_data is None:
             return
         signal_type = signal_data.get("type", "")
+        self._log_event("signal", signal=signal_type)
         if signal_type == "stop":
             print(
                 "\n>>> Stop signal received. Shutting down after current operation..."
@@ -281,7 +488,16 @@ class LoopRunner:
             sys.exit(1)
         if not self.config.prd_file.is_file():
             print(
-                f"Error: prd.json not found in {self.config.task_dir}", file=sys.stderr
+                f"Error: {self.config.prd_name} not found in {self.config.task_dir}",
+                file=sys.stderr,
+            )
+            sys.exit(1)
+        if not leaf_stories(self._read_prd()):
+            # Nothing to select would otherwise read as "all stories complete"
+            print(
+                f"Error: {self.config.prd_name} has no user stories. "
+                "Add at least one story to userStories, then rerun.",
+                file=sys.stderr,
             )
             sys.exit(1)
         if self.config.agent not in VALID_AGENTS:
@@ -329,8 +545,11 @@ class LoopRunner:
     def _handle_branch_completion(self, branch_config: BranchConfig) -> None:
         """Handle branch operations at loop completion."""
         try:
-            handle_completion(branch_config)
+            self._merge_result = handle_completion(
+                branch_config, allow_merge=self.config.allow_merge
+            )
         except BranchError as e:
+            self._merge_result = "failed"
             print(
                 f"  Warning: Branch completion failed: {e}",
                 file=sys.stderr,
@@ -338,11 +557,24 @@ class LoopRunner:
 
     def _read_prd(self) -> dict[str, Any]:
         """Read and parse prd.json, validating schema version."""
-        try:
-            prd = json.loads(self.config.prd_file.read_text())
-        except (json.JSONDecodeError, OSError) as e:
-            print(f"Error reading prd.json: {e}", file=sys.stderr)
-            sys.exit(1)
+        for attempt in range(PRD_READ_RETRIES + 1):
+            try:
+                prd = json.loads(self.config.prd_file.read_text())
+                break
+            except json.JSONDecodeError as e:
+                if attempt == PRD_READ_RETRIES:
+                    print(f"Error reading {self.config.prd_name}: {e}", file=sys.stderr)
+                    sys.exit(1)
+                if attempt == 0:
+                    print(
+                        f"Warning: {self.config.prd_name} temporarily unreadable, "
+                        "retrying...",
+                        file=sys.stderr,
+                    )
+                time.sleep(PRD_READ_RETRY_SECONDS)
+            except OSError as e:
+                print(f"Error reading {self.config.prd_name}: {e}", file=sys.stderr)
+                sys.exit(1)
 
         # Validate schema version
         schema_version = prd.get("schemaVersion", "1.0")
@@ -351,30 +583,107 @@ class LoopRunner:
             print(f"Error: {message}", file=sys.stderr)
             print(f"Current ralph supports schema versions up to {SCHEMA_VERSION}.")
             sys.exit(1)
-        if message:  # Warning for future versions
+        if message and not self._schema_warned:  # Warning for future versions
+            # prd.json is re-read many times per iteration; warn once per run
             print(f"Warning: {message}", file=sys.stderr)
+            self._schema_warned = True
 
+        self._story_counts = (self._count_completed(prd), len(leaf_stories(prd)))
         return prd
 
     def _get_next_story(self, prd: dict[str, Any]) -> dict[str, Any] | None:
-        """Get the highest priority story where passes is false."""
-        stories: list[dict[str, Any]] = prd.get("userStories", [])
-        incomplete = [s for s in stories if not s.get("passes", False)]
-        if not incomplete:
-            return None
+        """Get the highest priority story that is neither passing nor blocked."""
+        incomplete = self._workable_stories(prd)
+        return incomplete[0] if incomplete else None
+
+    def _workable_stories(self, prd: dict[str, Any]) -> list[dict[str, Any]]:
+        """Stories that are neither passing nor blocked, by priority."""
+        incomplete = [
+            s
+            for s in leaf_stories(prd)
+            if not s.get("passes", False) and not s.get("blocked", False)
+        ]
         incomplete.sort(key=lambda s: s.get("priority", 999))
-        result: dict[str, Any] = incomplete[0]
-        return result
+        return incomplete
 
     def _count_completed(self, prd: dict[str, Any]) -> int:
         """Count completed stories."""
-        stories = prd.get("userStories", [])
+        stories = leaf_stories(prd)
         return sum(1 for s in stories if s.get("passes", False))
 
+    def _count_blocked(self, prd: dict[str, Any]) -> int:
+        """Count incomplete stories marked as blocked on something external."""
+        stories = leaf_stories(prd)
+        return sum(
+            1
+            for s in stories
+            if s.get("blocked", False) and not s.get("passes", False)
+        )
+
+    def _passing_story_ids(self, prd: dict[str, Any]) -> set[str]:
+        """Get the ids of all stories currently marked as passing."""
+        stories = leaf_stories(prd)
+        return {str(s.get("id", "")) for s in stories if s.get("passes", False)}
+
+    @property
+    def _max_iterations_label(self) -> str:
+        """The max iterations for display ("unlimited" for -i unlimited)."""
+        return format_max_iterations(self.config.max_iterations)
+
+    @property
+    def _max_iterations_json(self) -> int | None:
+        """The max iterations for JSON output (null for -i unlimited)."""
+        if self.config.max_iterations >= UNLIMITED_ITERATIONS:
+            return None
+        return self.config.max_iterations
+
+    def _finish(self, outcome: str, **fields: Any) -> None:
+        """Record how the loop ended and log it as the final event."""
+        self._outcome = outcome
+        self._log_event(outcome, **fields)
+
+    def _log_event(self, event: str, **fields: Any) -> None:
+        """Append an event to the --event-log file, if one is configured.
+
+        Every event carries the current iteration and the story counts from
+        the most recent prd.json read; logging never reads the file itself.
+        """
+        if self._events is None:
+            return
+        completed, total = self._story_counts
+        self._events.append(
+            event,
+            task=self._task_name,
+            iteration=self.current_iteration,
+            completed=completed,
+            total=total,
+            **fields,
+        )
+
     def _resolve_agent_name(self, prd: dict[str, Any], story: dict[str, Any]) -> str:
         """Resolve which agent to use for this iteration."""
         return resolve_agent(prd, story, self.config.agent_override)
 
+    def _warn_if_marker_ignored(self, agent_name: str) -> None:
+        """Warn once when a custom --complete-marker meets an opencode run.
+
+        A per-story agent or failover can switch to opencode mid-run. Its
+        terminal and server modes never scan output for a marker; they rely
+        on prd.json alone.
+        """
+        if (
+            agent_name == "claude"
+            or self.config.completion_signal == COMPLETION_SIGNAL
+            or self._marker_warned
+        ):
+            return
+        print(
+            f"Warning: --complete-marker is ignored for {agent_name} iterations; "
+            f"they finish when {self.config.prd_name} shows all stories passing.",
+            file=sys.stderr,
+        )
+        self._marker_warned = True
+
     def _run_agent(self, agent_name: str, story: dict[str, Any]) -> AgentResult:
         """Run the agent for one iteration.
 
@@ -394,6 +703,11 @@ class LoopRunner:
 
         # Terminal mode: agent inherits the terminal (tmux pane)
         agent = create_agent(agent_name)
+        capture_file = None
+        if self.config.capture_dir:
+            capture_file = (
+                self.config.capture_dir / f"iteration-{self.current_iteration}.log"
+            )
 
         agent_config = AgentConfig(
             prompt=prompt,
@@ -401,6 +715,11 @@ class LoopRunner:
             yolo_mode=self.config.yolo_mode,
             verbose=self.config.verbose,
             model=self.config.model or "",
+            settings_file=self.config.settings_file,
+            extra_env=self.config.agent_env,
+            capture_file=capture_file,
+            idle_timeout=self.config.idle_timeout,
+            completion_signal=self.config.completion_signal,
         )
 
         return agent.run_in_terminal(agent_config)
@@ -445,7 +764,7 @@ class LoopRunner:
             expected_msg_count = initial_msg_count + 2 if initial_msg_count >= 0 else -1
 
             # Poll for completion while checking stop signals
-            poll_interval = 0.5  # seconds between status checks
+            poll_interval = self.config.poll_ms / 1000  # seconds between checks
             aborted = False
             user_interaction_detected = False
 
@@ -469,6 +788,7 @@ class LoopRunner:
                         completed=False,
                         failed=True,
                         error_message="OpenCode server not responding",
+                        process_error=True,
                     )
 
                 # Poll session status
@@ -549,12 +869,19 @@ class LoopRunner:
                 completed=False,
                 failed=True,
                 error_message=f"OpenCode server error: {e}",
+                process_error=True,
             )
 
     def _build_prompt(self, agent_name: str) -> str:
         """Build the prompt for the agent using the prompt module."""
         prd = self._read_prd()
         branch_name = str(prd.get("branchName", ""))
+        focus_stories: list[tuple[str, str]] = []
+        if self.config.incomplete_only:
+            focus_stories = [
+                (str(s.get("id", "")), str(s.get("title", "")))
+                for s in self._workable_stories(prd)
+            ]
 
         context = PromptContext(
             task_dir=self.config.task_dir,
@@ -562,8 +889,93 @@ class LoopRunner:
             progress_file=self.config.progress_file,
             branch_name=branch_name,
             agent=agent_name,
+            prompt_file=self.config.prompt_file,
+            focus_stories=focus_stories,
+            previous_iteration=self._previous_iteration,
+            allow_merge=self.config.allow_merge,
         )
-        return build_prompt(context)
+        try:
+            return build_prompt(context)
+        except PromptError as e:
+            print(f"Error: {e}", file=sys.stderr)
+            sys.exit(1)
+
+    def _summarize_iteration(
+        self,
+        iteration: int,
+        story_id: str,
+        agent: str,
+        result: AgentResult,
+        changes: list[str],
+    ) -> str:
+        """Describe how an iteration went, for the next iteration's prompt."""
+        minutes, seconds = divmod(int(result.duration_seconds), 60)
+        lines = [
+            f"Iteration {iteration} worked on {story_id} with {agent} "
+            f"for {minutes}m {seconds:02d}s."
+        ]
+        if result.failed:
+            reason = result.error_message or f"exit code {result.exit_code}"
+            lines.append(f"It failed: {reason}")
+        if changes:
+            lines.append(f"{self.config.prd_name} changes:")
+            lines.extend(f"- {change}" for change in changes)
+        else:
+            lines.append(f"It made no {self.config.prd_name} changes.")
+        return "\n".join(lines)
+
+    def _check_prompt_changed(self) -> None:
+        """Report when prompt.md was edited since the previous iteration.
+
+        The prompt is rebuilt for every iteration, so edits made mid-run are
+        picked up automatically. This just makes the change visible.
+        """
+        try:
+            prompt_path = resolve_prompt_path(
+                self.config.task_dir, self.config.prompt_file
+            )
+            mtime = prompt_path.stat().st_mtime if prompt_path else None
+        except (PromptError, OSError):
+            # A missing --prompt file is reported when the prompt is built
+            return
+
+        state = (prompt_path, mtime)
+        if self._prompt_state is not None and state != self._prompt_state:
+            source = prompt_path or "built-in default"
+            print(f"  prompt.md updated - applying this iteration ({source})")
+        self._prompt_state = state
+
+    def _classify_failure(self, result: AgentResult) -> str | None:
+        """Classify a failed result as auth/credit/rate-limit/overloaded.
+
+        Only errors reported by the CLI or API are classified. A message
+        pulled from the agent's own output (say, a summary mentioning a 401
+        handler) could otherwise stop the run as an auth failure.
+        """
+        if not result.failed or not result.process_error:
+            return None
+        return classify_error(result.error_message)
+
+    def _wait_for_rate_limit(self, error_kind: str) -> None:
+        """Back off before retrying a rate-limited or overloaded agent.
+
+        Stop/checkpoint signals are honored during the wait.
+        """
+        self._rate_limit_streak += 1
+        delay = min(
+            RATE_LIMIT_BACKOFF_SECONDS * 2 ** (self._rate_limit_streak - 1),
+            RATE_LIMIT_BACKOFF_MAX_SECONDS,
+        )
+        reason = "Rate limited" if error_kind == "rate_limit" else "API overloaded"
+        print(f"\n  {reason}. Waiting {delay}s before retrying...")
+        print(f"  (ralph stop {self._task_name} to stop instead)")
+        self._log_event("backoff", error_kind=error_kind, seconds=delay)
+        deadline = time.time() + delay
+        while time.time() < deadline:
+            self._check_signals()
+            if self._shutdown_requested:
+                return
+            time.sleep(1)
 
     def _handle_failure(
         self, agent: str, story: dict[str, Any], result: AgentResult, iteration: int
@@ -739,13 +1151,13 @@ class LoopRunner:
         """Handle graceful shutdown by writing checkpoint."""
         prd = self._read_prd()
         completed = self._count_completed(prd)
-        total = len(prd.get("userStories", []))
+        total = len(leaf_stories(prd))
         now = datetime.now().strftime("%Y-%m-%d %H:%M")
 
         entry = (
             f"\n---\n"
             f"CHECKPOINT at {now}\n"
-            f"Iteration: {self.current_iteration}/{self.config.max_iterations} | "
+            f"Iteration: {self.current_iteration}/{self._max_iterations_label} | "
             f"Stories: {completed}/{total} | Agent: {self.current_agent}\n"
             f"Reason: shutdown signal\n"
             f"---\n"
@@ -757,9 +1169,17 @@ class LoopRunner:
         """Print the startup banner."""
         prd = self._read_prd()
         completed = self._count_completed(prd)
-        total = len(prd.get("userStories", []))
+        total = len(leaf_stories(prd))
+        blocked = self._count_blocked(prd)
         description = prd.get("description", "No description")
         branch = prd.get("branchName", "unknown")
+        merge_target = prd.get("mergeTarget")
+        if merge_target:
+            branch = f"{branch} → {merge_target}"
+            if prd.get("autoMerge", False) and self.config.allow_merge:
+                branch += " (auto-merge on completion)"
+            elif prd.get("autoMerge", False):
+                branch += " (PR only: autoMerge needs --allow-merge)"
 
         print()
         print("=" * 67)
@@ -768,25 +1188,65 @@ class LoopRunner:
         print()
         print(f"  Task:       {self.config.task_dir}")
         print(f"  Branch:     {branch}")
+        print(f"  Type:       {parse_prd_type(prd)}")
         print(f"  Agent:      {self.current_agent}")
-        print(f"  Progress:   {completed} / {total} stories complete")
-        print(f"  Max iters:  {self.config.max_iterations}")
+        progress = f"{completed} / {total} stories complete"
+        if blocked:
+            progress += f" ({blocked} blocked)"
+        print(f"  Progress:   {progress}")
+        print(f"  Max iters:  {self._max_iterations_label}")
+        print(f"  Prompt:     {self._prompt_source()}")
         print()
         print(f"  {description}")
         print()
 
+        for warning in validate_prd(prd):
+            print(f"Warning: {self.config.prd_name}: {warning}", file=sys.stderr)
+
+    def _prompt_source(self) -> str:
+        """Describe which prompt template this run will use.
+
+        Falling back to the built-in default is almost never intended, so it
+        is flagged as a warning.
+        """
+        try:
+            prompt_path = resolve_prompt_path(
+                self.config.task_dir, self.config.prompt_file
+            )
+        except PromptError as e:
+            return str(e)
+        if prompt_path is None:
+            print(
+                "Warning: No prompt.md found, using the built-in default prompt.",
+                file=sys.stderr,
+            )
+            return "built-in default"
+        return str(prompt_path)
+
     def _print_iteration_header(
         self, iteration: int, completed: int, total: int, story: dict[str, Any]
     ) -> None:
         """Print the iteration header."""
         story_id = story.get("id", "?")
         story_title = story.get("title", "?")
+        if story.get("parentId"):
+            story_title = f"{story_title} (epic {story['parentId']})"
         print()
         print("=" * 67)
         print(
-            f"  Iteration {iteration} of {self.config.max_iterations} "
+            f"  Iteration {iteration} of {self._max_iterations_label} "
             f"({completed}/{total} complete) - {story_id}: {story_title}"
         )
+        eta = estimate_remaining_seconds(
+            self._completion_times, self._start_time, total - completed
+        )
+        if eta is not None:
+            print(f"  ETA: {_format_eta(eta)} (rough, from this run's pace)")
+        if self.config.stall_limit > 0 and self._stall_streak > 0:
+            print(
+                f"  No progress for {self._stall_streak} of "
+                f"{self.config.stall_limit} allowed iterations"
+            )
         print("=" * 67)
 
     def _print_complete(self, iteration: int) -> None:
@@ -796,14 +1256,171 @@ class LoopRunner:
         print("  Ralph completed all tasks!")
         print("=" * 67)
         print()
-        print(f"  Completed at iteration {iteration} of {self.config.max_iterations}")
+        print(f"  Completed at iteration {iteration} of {self._max_iterations_label}")
+        print()
+
+    def _print_startup_failure(self, agent: str, result: AgentResult) -> None:
+        """Print details for an agent that exited right after starting."""
+        print(file=sys.stderr)
+        print("=" * 67, file=sys.stderr)
+        print(
+            f"  {agent} exited after {result.duration_seconds:.1f}s "
+            f"(exit code {result.exit_code})",
+            file=sys.stderr,
+        )
+        print("=" * 67, file=sys.stderr)
+        print(file=sys.stderr)
+        print(f"  Error: {result.error_message}", file=sys.stderr)
+        output_tail = result.output.strip().splitlines()[-20:]
+        if output_tail:
+            print(file=sys.stderr)
+            for line in output_tail:
+                print(f"  {line}", file=sys.stderr)
+        print(file=sys.stderr)
+        print(
+            "  Stopping instead of retrying. Check the agent's flags and "
+            "authentication, then run again.",
+            file=sys.stderr,
+        )
+
+    def _print_stalled(self) -> None:
+        """Print the stall-limit stop message."""
+        prd = self._read_prd()
+        completed = self._count_completed(prd)
+        total = len(leaf_stories(prd))
+
+        print()
+        print("=" * 67)
+        print("  Ralph stopped: stalled - no progress")
+        print("=" * 67)
+        print()
+        print(
+            f"  No story or acceptance criterion passed in the last "
+            f"{self._stall_streak} iterations (--stall-limit "
+            f"{self.config.stall_limit})."
+        )
+        print(f"  Completed {completed} of {total} stories.")
+        print()
+        print("  Check progress.txt and the PRD notes before running again.")
+
+    def _print_summary(self) -> dict[str, Any]:
+        """Print the end-of-run summary (as JSON with --summary-json).
+
+        Returns the summary fields, for _notify().
+        """
+        prd = self._read_prd()
+        stories = leaf_stories(prd)
+        incomplete = [
+            str(s.get("id", "")) for s in stories if not s.get("passes", False)
+        ]
+        runtime = time.time() - self._start_time
+        summary: dict[str, Any] = {
+            "outcome": self._outcome,
+            "runtime_seconds": round(runtime, 1),
+            "iterations": self._iterations_run,
+            "max_iterations": self._max_iterations_json,
+            "stories_completed": self._count_completed(prd),
+            "stories_total": len(stories),
+            "incomplete_stories": incomplete,
+        }
+        if self._merge_result is not None:
+            summary["merge"] = self._merge_result
+
+        if self.config.summary_json:
+            print(json.dumps(summary))
+            return summary
+
+        minutes, seconds = divmod(int(runtime), 60)
+        hours, minutes = divmod(minutes, 60)
+        print()
+        print("--- Run summary " + "-" * 51)
+        print(f"  Outcome:     {self._outcome}")
+        print(f"  Runtime:     {hours}h {minutes:02d}m {seconds:02d}s")
+        print(f"  Iterations:  {self._iterations_run} / {self._max_iterations_label}")
+        print(
+            f"  Stories:     {summary['stories_completed']} / "
+            f"{summary['stories_total']} complete"
+        )
+        if incomplete:
+            print(f"  Remaining:   {', '.join(incomplete)}")
+        if self._merge_result is not None:
+            print(f"  Merge:       {self._merge_result}")
+        print()
+        return summary
+
+    def _notify(self, summary: dict[str, Any]) -> None:
+        """POST the run summary to --notify-url, if one is configured.
+
+        The payload is the summary plus the task name and a one-line "text"
+        field, which Slack-style incoming webhooks display as the message.
+        Delivery failures are reported but never change the exit code.
+        """
+        url = self.config.notify_url
+        if not url:
+            return
+        if self.config.notify_on == "complete" and self._outcome != "complete":
+            return
+
+        payload = {
+            "task": self._task_name,
+            "text": (
+                f"ralph: {self._task_name} {self._outcome} "
+                f"({summary['stories_completed']}/{summary['stories_total']} "
+                "stories)"
+            ),
+            **summary,
+        }
+        request = Request(
+            url,
+            data=json.dumps(payload).encode(),
+            headers={"Content-Type": "application/json"},
+            method="POST",
+        )
+        try:
+            with urlopen(request, timeout=NOTIFY_TIMEOUT_SECONDS):
+                pass
+        except OSError as e:  # URLError, timeouts and refused connections
+            print(f"Warning: --notify-url delivery failed: {e}", file=sys.stderr)
+
+    def _print_agent_error(
+        self, agent: str, error_kind: str, result: AgentResult
+    ) -> None:
+        """Print the banner for an error that retrying cannot fix."""
+        title = "authentication failed" if error_kind == "auth" else "out of credit"
+        print(file=sys.stderr)
+        print("=" * 67, file=sys.stderr)
+        print(f"  {agent}: {title}", file=sys.stderr)
+        print("=" * 67, file=sys.stderr)
+        print(file=sys.stderr)
+        print(f"  Error: {result.error_message}", file=sys.stderr)
+        for line in result.output.strip().splitlines()[-5:]:
+            print(f"  {line}", file=sys.stderr)
+        print(file=sys.stderr)
+        print(f"  {ERROR_HINTS[error_kind]}", file=sys.stderr)
+        print(file=sys.stderr)
+        print("  Stopping instead of retrying into the same error.", file=sys.stderr)
+
+    def _print_all_blocked(self, prd: dict[str, Any]) -> None:
+        """Print the stop message when only blocked stories remain."""
+        blocked_ids = [
+            str(s.get("id", ""))
+            for s in leaf_stories(prd)
+            if s.get("blocked", False) and not s.get("passes", False)
+        ]
+        print()
+        print("=" * 67)
+        print("  Ralph stopped: all remaining stories are blocked")
+        print("=" * 67)
+        print()
+        print(f"  Blocked: {', '.join(blocked_ids)}")
         print()
+        print('  Clear "blocked" in prd.json once they can proceed, then rerun.')
 
     def _print_max_iterations(self) -> None:
         """Print max iterations reached message."""
         prd = self._read_prd()
         completed = self._count_completed(prd)
-        total = len(prd.get("userStories", []))
+        total = len(leaf_stories(prd))
 
         print()
         print("=" * 67)
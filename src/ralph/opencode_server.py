@@ -33,10 +33,10 @@ def _get_logger() -> logging.Logger:
     if not logger.handlers:
         log_dir = Path.home() / ".local" / "state" / "ralph"
         log_dir.mkdir(parents=True, exist_ok=True)
-        handler = logging.FileHandler(log_dir / "opencode-server.log")
+        # Opened on first record, so `--log-level off` leaves no file behind
+        handler = logging.FileHandler(log_dir / "opencode-server.log", delay=True)
         handler.setFormatter(logging.Formatter("%(asctime)s %(levelname)s %(message)s"))
         logger.addHandler(handler)
-        logger.setLevel(logging.DEBUG)
     return logger
 
 
@@ -143,7 +143,7 @@ class OpencodeClient:
         Returns an OpencodeSession with the session ID.
         """
         url = self._url_with_directory("/session")
-        self._log.info("Creating session: POST %s", url)
+        self._log.debug("Creating session: POST %s", url)
 
         # Use provided permissions or default to allow-all for autonomous operation
         ruleset = permissions if permissions is not None else self.DEFAULT_PERMISSIONS
@@ -365,7 +365,7 @@ class OpencodeClient:
         Note: Returns immediately. Git reset and startup scripts run async.
         """
         url = self._url_with_directory("/experimental/worktree")
-        self._log.info("Creating worktree: POST %s (name=%s)", url, name)
+        self._log.debug("Creating worktree: POST %s (name=%s)", url, name)
 
         payload: dict[str, str] = {}
         if name:
@@ -393,7 +393,7 @@ class OpencodeClient:
         Returns a list of worktree directory paths.
         """
         url = self._url_with_directory("/experimental/worktree")
-        self._log.info("Listing worktrees: GET %s", url)
+        self._log.debug("Listing worktrees: GET %s", url)
         result = self._http_get(url)
         if result is None:
             return []
@@ -411,7 +411,7 @@ class OpencodeClient:
             True on success.
         """
         url = self._url_with_directory("/experimental/worktree/reset")
-        self._log.info("Resetting worktree: POST %s (directory=%s)", url, directory)
+        self._log.debug("Resetting worktree: POST %s (directory=%s)", url, directory)
         self._http_post(url, {"directory": directory})
         self._log.info("Worktree reset: %s", directory)
         return True
@@ -426,7 +426,7 @@ class OpencodeClient:
             True on success.
         """
         url = self._url_with_directory("/experimental/worktree")
-        self._log.info("Removing worktree: DELETE %s (directory=%s)", url, directory)
+        self._log.debug("Removing worktree: DELETE %s (directory=%s)", url, directory)
         self._http_delete(url, {"directory": directory})
         self._log.info("Worktree removed: %s", directory)
         return True
@@ -0,0 +1,220 @@
+"""prd.json helpers for Ralph agent runner.
+
+Holds what Ralph knows about a PRD's contents: the effort type, epic and
+leaf stories, progress counting, story-level diffs between iterations,
+authoring checks (validate_prd) and the starter PRD used by `ralph run`.
+Schema version checks live in version.py.
+"""
+
+from __future__ import annotations
+
+from enum import StrEnum
+from typing import Any
+
+from ralph.version import SCHEMA_VERSION
+
+DEFAULT_PRD_NAME = "prd.json"  # Overridden per run with --prd-file
+
+
+class PrdType(StrEnum):
+    """Known values of the prd.json "type" field."""
+
+    FEATURE = "feature"
+    BUG_INVESTIGATION = "bug-investigation"
+    INVESTIGATION = "investigation"
+
+
+def parse_prd_type(prd: dict[str, Any]) -> PrdType | str:
+    """Read the effort type from prd.json.
+
+    Args:
+        prd: Parsed prd.json data.
+
+    Returns:
+        The matching PrdType (FEATURE when the field is missing), or the raw
+        string for values Ralph does not know about.
+    """
+    raw = str(prd.get("type") or PrdType.FEATURE).strip()
+    try:
+        return PrdType(raw.lower())
+    except ValueError:
+        return raw
+
+
+def leaf_stories(prd: dict[str, Any]) -> list[dict[str, Any]]:
+    """Return the stories that are worked on directly.
+
+    A story named as another story's parentId is an epic. Its progress is
+    derived from its children, so it is left out of story counts and
+    next-story selection.
+
+    Args:
+        prd: Parsed prd.json data.
+
+    Returns:
+        Stories that are not the parent of any other story, in file order.
+    """
+    stories: list[dict[str, Any]] = prd.get("userStories", [])
+    parent_ids = {str(s["parentId"]) for s in stories if s.get("parentId")}
+    return [s for s in stories if str(s.get("id", "")) not in parent_ids]
+
+
+def validate_prd(prd: dict[str, Any]) -> list[str]:
+    """Check prd.json stories for common authoring mistakes.
+
+    Reported problems:
+    - Duplicate priorities (next-story order then depends on file order)
+    - Stories without a priority (epics are exempt)
+    - Empty story ids or titles
+    - Stories with no acceptance criteria (epics are exempt)
+    - parentId values that match no story
+    - Unknown "type" values
+
+    Args:
+        prd: Parsed prd.json data.
+
+    Returns:
+        Human-readable warnings, empty if the PRD looks fine.
+    """
+    warnings: list[str] = []
+    stories: list[dict[str, Any]] = prd.get("userStories", [])
+    story_ids = {str(s.get("id", "")) for s in stories}
+    leaf_ids = {str(s.get("id", "")) for s in leaf_stories(prd)}
+    by_priority: dict[Any, list[str]] = {}
+
+    prd_type = parse_prd_type(prd)
+    if not isinstance(prd_type, PrdType):
+        known = ", ".join(t.value for t in PrdType)
+        warnings.append(f"Unknown type '{prd_type}' (expected one of: {known})")
+
+    for index, story in enumerate(stories, start=1):
+        story_id = str(story.get("id", "")).strip()
+        label = story_id or f"Story #{index}"
+
+        if not story_id:
+            warnings.append(f"Story #{index} has an empty id")
+        if not str(story.get("title", "")).strip():
+            warnings.append(f"{label} has an empty title")
+        if story_id in leaf_ids and not story.get("acceptanceCriteria"):
+            warnings.append(f"{label} has no acceptance criteria")
+        parent_id = story.get("parentId")
+        if parent_id and str(parent_id) not in story_ids:
+            warnings.append(f"{label} has unknown parentId {parent_id}")
+
+        if story_id not in leaf_ids:
+            continue  # Epics are never selected, so their priority is unused
+        priority = story.get("priority")
+        if priority is None:
+            warnings.append(f"{label} has no priority")
+        else:
+            by_priority.setdefault(priority, []).append(label)
+
+    for priority, labels in by_priority.items():
+        if len(labels) > 1:
+            warnings.append(f"Priority {priority} is shared by {', '.join(labels)}")
+
+    return warnings
+
+
+def count_progress(prd: dict[str, Any]) -> int:
+    """Count passing stories plus passing acceptance criteria.
+
+    Used to detect iterations that made no progress at all. Only leaf
+    stories are counted (see leaf_stories). Criteria are
+    only counted when they carry their own "passes" flag (schema 2.x);
+    plain-string criteria contribute nothing.
+
+    Args:
+        prd: Parsed prd.json data.
+
+    Returns:
+        Number of passing stories and criteria.
+    """
+    total = 0
+    for story in leaf_stories(prd):
+        if story.get("passes", False):
+            total += 1
+        for criterion in story.get("acceptanceCriteria", []):
+            if isinstance(criterion, dict) and criterion.get("passes", False):
+                total += 1
+    return total
+
+
+def diff_prd(old: dict[str, Any], new: dict[str, Any]) -> list[str]:
+    """Describe story-level changes between two versions of prd.json.
+
+    Args:
+        old: prd.json data before the iteration.
+        new: prd.json data after the iteration.
+
+    Returns:
+        One message per change (e.g. "US-007 marked complete"), in the
+        story order of the new file, followed by removed stories.
+    """
+    old_stories = {str(s.get("id", "")): s for s in old.get("userStories", [])}
+    new_stories = {str(s.get("id", "")): s for s in new.get("userStories", [])}
+    changes: list[str] = []
+
+    for story_id, story in new_stories.items():
+        before = old_stories.get(story_id)
+        if before is None:
+            changes.append(f"{story_id} added: {story.get('title', '')}")
+            continue
+        passes = story.get("passes", False)
+        if passes != before.get("passes", False):
+            state = "complete" if passes else "incomplete"
+            changes.append(f"{story_id} marked {state}")
+        blocked = story.get("blocked", False)
+        if blocked != before.get("blocked", False):
+            changes.append(f"{story_id} {'blocked' if blocked else 'unblocked'}")
+
+    for story_id in old_stories:
+        if story_id not in new_stories:
+            changes.append(f"{story_id} removed")
+
+    return changes
+
+
+def new_prd(
+    project: str, task_dir: str, description: str, story_title: str
+) -> dict[str, Any]:
+    """Build a minimal prd.json with a single story.
+
+    Used when `ralph run` finds a task directory without a PRD. The result
+    passes validate_prd, so it can be run as-is and fleshed out later.
+
+    Args:
+        project: Project name.
+        task_dir: Task directory relative to the project root (tasks/<name>).
+        description: One-line description of the effort.
+        story_title: Title of the first story.
+
+    Returns:
+        prd.json data at the current schema version.
+    """
+    effort_name = task_dir.rstrip("/").rsplit("/", 1)[-1]
+    return {
+        "schemaVersion": SCHEMA_VERSION,
+        "project": project,
+        "taskDir": task_dir,
+        "branchName": f"ralph/{effort_name}",
+        "mergeTarget": None,
+        "autoMerge": False,
+        "pauseBetweenStories": False,
+        "type": PrdType.FEATURE.value,
+        "description": description,
+        "notes": "",
+        "userStories": [
+            {
+                "id": "US-001",
+                "title": story_title,
+                "description": "",
+                "acceptanceCriteria": [
+                    {"description": "Typecheck passes", "passes": False},
+                ],
+                "priority": 1,
+                "passes": False,
+                "notes": "",
+            },
+        ],
+    }
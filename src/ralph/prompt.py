@@ -11,6 +11,10 @@ from dataclasses import dataclass, field
 from pathlib import Path
 
 
+class PromptError(Exception):
+    """Raised when the prompt template cannot be loaded."""
+
+
 @dataclass
 class PromptContext:
     """Context variables available for template substitution."""
@@ -20,7 +24,12 @@ class PromptContext:
     progress_file: Path
     branch_name: str = ""
     agent: str = "claude"
+    prompt_file: Path | None = None  # CLI --prompt override
     extra_vars: dict[str, str] = field(default_factory=dict)
+    # (id, title) of the stories to focus on (--incomplete-only); empty = all
+    focus_stories: list[tuple[str, str]] = field(default_factory=list)
+    previous_iteration: str = ""  # Outcome of the last iteration in this run
+    allow_merge: bool = False  # CLI --allow-merge; shown as the Auto-merge line
 
     def to_vars(self) -> dict[str, str]:
         """Convert context to a variable dictionary for substitution."""
@@ -35,45 +44,74 @@ class PromptContext:
         return variables
 
 
-def load_prompt_template(task_dir: Path) -> str:
-    """Load prompt.md template from configured locations.
+DEFAULT_PROMPT_TEMPLATE = (
+    "# No prompt template found\nImplement the next story from prd.json."
+)
+
+
+def resolve_prompt_path(
+    task_dir: Path, prompt_file: Path | None = None
+) -> Path | None:
+    """Find the prompt.md file that will be used for a task.
 
     Search order:
+    0. Explicit override (--prompt), which must exist
     1. Task directory (task_dir/prompt.md)
     2. User config (~/.config/ralph/prompt.md)
     3. Project root (task_dir/../../prompt.md, i.e., two levels up from tasks/foo/)
     4. Installed location (~/.local/share/ralph/prompt.md)
-    5. Fallback: minimal default prompt
 
     Args:
         task_dir: Path to the task directory containing prd.json.
+        prompt_file: Explicit prompt file override. Never falls back.
 
     Returns:
-        The prompt template content as a string.
+        Path to the prompt file, or None if the built-in default will be used.
+
+    Raises:
+        PromptError: If prompt_file is given but does not exist.
     """
-    # 1. Task directory
-    task_prompt = task_dir / "prompt.md"
-    if task_prompt.is_file():
-        return task_prompt.read_text()
+    if prompt_file is not None:
+        if not prompt_file.is_file():
+            raise PromptError(f"Prompt file not found: {prompt_file}")
+        return prompt_file
+
+    candidates = [
+        # 1. Task directory
+        task_dir / "prompt.md",
+        # 2. User config
+        Path.home() / ".config" / "ralph" / "prompt.md",
+        # 3. Project root (tasks/ is typically one level under project root)
+        task_dir.parent.parent / "prompt.md",
+        # 4. Installed location
+        Path.home() / ".local" / "share" / "ralph" / "prompt.md",
+    ]
+    for candidate in candidates:
+        if candidate.is_file():
+            return candidate
+    return None
+
+
+def load_prompt_template(task_dir: Path, prompt_file: Path | None = None) -> str:
+    """Load prompt.md template from configured locations.
 
-    # 2. User config
-    config_prompt = Path.home() / ".config" / "ralph" / "prompt.md"
-    if config_prompt.is_file():
-        return config_prompt.read_text()
+    Uses the first file found by resolve_prompt_path(), falling back to a
+    minimal default prompt when none exists.
 
-    # 3. Project root (tasks/ is typically one level under project root)
-    project_root = task_dir.parent.parent
-    root_prompt = project_root / "prompt.md"
-    if root_prompt.is_file():
-        return root_prompt.read_text()
+    Args:
+        task_dir: Path to the task directory containing prd.json.
+        prompt_file: Explicit prompt file override (--prompt).
 
-    # 4. Installed location
-    installed_prompt = Path.home() / ".local" / "share" / "ralph" / "prompt.md"
-    if installed_prompt.is_file():
-        return installed_prompt.read_text()
+    Returns:
+        The prompt template content as a string.
 
-    # 5. Fallback
-    return "# No prompt template found\nImplement the next story from prd.json."
+    Raises:
+        PromptError: If prompt_file is given but does not exist.
+    """
+    prompt_path = resolve_prompt_path(task_dir, prompt_file)
+    if prompt_path is None:
+        return DEFAULT_PROMPT_TEMPLATE
+    return prompt_path.read_text()
 
 
 def substitute_variables(template: str, variables: dict[str, str]) -> str:
@@ -203,7 +241,9 @@ def build_prompt(context: PromptContext) -> str:
     3. Applies variable substitution
     4. Injects AGENTS.md content
     5. Injects prd.json notes (if present)
-    6. Prepends the task context header
+    6. Injects the focused story list (if present)
+    7. Injects the previous iteration's outcome (if present)
+    8. Prepends the task context header
 
     Args:
         context: The prompt context with task info and variables.
@@ -212,7 +252,7 @@ def build_prompt(context: PromptContext) -> str:
         The fully assembled prompt string ready for the agent.
     """
     # Load template
-    template = load_prompt_template(context.task_dir)
+    template = load_prompt_template(context.task_dir, context.prompt_file)
 
     # Preprocess agent-specific sections
     template = preprocess_agent_sections(template, context.agent)
@@ -229,11 +269,13 @@ def build_prompt(context: PromptContext) -> str:
 
     # Build the final prompt with header
     task_dir_str = str(context.task_dir)
+    auto_merge = "allowed" if context.allow_merge else "not allowed"
     header = (
         f"# Ralph Agent Instructions\n\n"
         f"Task Directory: {task_dir_str}\n"
-        f"PRD File: {task_dir_str}/prd.json\n"
-        f"Progress File: {task_dir_str}/progress.txt\n\n"
+        f"PRD File: {context.prd_file}\n"
+        f"Progress File: {task_dir_str}/progress.txt\n"
+        f"Auto-merge: {auto_merge}\n\n"
     )
 
     # Insert AGENTS.md before the main prompt content if present
@@ -255,4 +297,28 @@ def build_prompt(context: PromptContext) -> str:
     else:
         notes_section = ""
 
-    return f"{header}{notes_section}{agents_section}{template}\n"
+    # List the remaining stories so completed ones can be skipped entirely
+    if context.focus_stories:
+        story_lines = "\n".join(
+            f"- {story_id}: {title}" for story_id, title in context.focus_stories
+        )
+        focus_section = (
+            f"## Remaining Stories\n\n"
+            f"Only these stories are left. Completed stories in the PRD need no "
+            f"further attention.\n\n{story_lines}\n\n---\n\n"
+        )
+    else:
+        focus_section = ""
+
+    # What the last iteration of this run did, since each one starts fresh
+    if context.previous_iteration:
+        previous_section = (
+            f"## Previous Iteration\n\n{context.previous_iteration}\n\n---\n\n"
+        )
+    else:
+        previous_section = ""
+
+    return (
+        f"{header}{notes_section}{focus_section}{previous_section}"
+        f"{agents_section}{template}\n"
+    )
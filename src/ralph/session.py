@@ -35,6 +35,16 @@ TMUX_PREFIX = "ralph-"
 # Signal file for stop/checkpoint communication
 SIGNAL_DIR = DATA_DIR / "signals"
 
+# max_iterations value for `ralph run -i unlimited` (run until complete)
+UNLIMITED_ITERATIONS = sys.maxsize
+
+
+def format_max_iterations(max_iterations: int) -> str:
+    """Format a max-iterations value, showing "unlimited" for -i unlimited."""
+    if max_iterations >= UNLIMITED_ITERATIONS:
+        return "unlimited"
+    return str(max_iterations)
+
 
 @dataclass
 class SessionInfo:
@@ -330,7 +340,8 @@ def tmux_create_session(
         session_name: Name for the tmux session.
         command: Shell command string to run in the session.
         cwd: Working directory for the session.
-        environment: Environment variables to set in the session.
+        environment: Environment variables to set in the session. They are
+            prefixed to the command, so ps shows them; never pass secrets.
         width: Terminal width (default 200).
         height: Terminal height (default 50).
 
@@ -381,12 +392,18 @@ def tmux_kill_session(session_name: str) -> None:
         pass
 
 
-def tmux_attach_session(session_name: str) -> int:
+def tmux_attach_session(session_name: str, read_only: bool = False) -> int:
     """Attach to a tmux session (takes over terminal).
 
+    With read_only, tmux ignores keyboard input from this client, so the
+    agent can be watched without risk of typing into it.
+
     Returns the exit code from tmux attach.
     """
-    result = subprocess.run(["tmux", "attach-session", "-t", session_name])
+    cmd = ["tmux", "attach-session", "-t", session_name]
+    if read_only:
+        cmd.append("-r")
+    result = subprocess.run(cmd)
     return result.returncode
 
 
@@ -591,7 +608,10 @@ def get_status(as_json: bool = False, db: SessionDB | None = None) -> str:
     lines.append("-" * 80)
 
     for s in sessions:
-        iter_str = f"{s.iteration}/{s.max_iterations}"
+        if s.max_iterations >= UNLIMITED_ITERATIONS:
+            iter_str = f"{s.iteration}/∞"  # "unlimited" would overflow the column
+        else:
+            iter_str = f"{s.iteration}/{s.max_iterations}"
         story = s.current_story or "-"
         # Show port only for running sessions to avoid confusion.
         # Dead/stopped sessions showing ports misleads users.
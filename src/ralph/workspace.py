@@ -40,10 +40,10 @@ def _get_logger() -> logging.Logger:
     if not logger.handlers:
         log_dir = Path.home() / ".local" / "state" / "ralph"
         log_dir.mkdir(parents=True, exist_ok=True)
-        handler = logging.FileHandler(log_dir / "workspace.log")
+        # Opened on first record, so `--log-level off` leaves no file behind
+        handler = logging.FileHandler(log_dir / "workspace.log", delay=True)
         handler.setFormatter(logging.Formatter("%(asctime)s %(levelname)s %(message)s"))
         logger.addHandler(handler)
-        logger.setLevel(logging.DEBUG)
     return logger
 
 